@@ -0,0 +1,295 @@
+#![allow(clippy::to_string_trait_impl)]
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::game_database::GameDatabase;
+use crate::twotech_object::RecipeStep;
+
+// Recipes that feed back into themselves keep asking for more, so give up on a bill that hasn't settled after this many crafts
+const MAX_EXPANSIONS: usize = 10000;
+
+/// Everything needed to make some number of an object, following its twotech recipe
+#[derive(Clone, Debug, Serialize)]
+pub struct BillOfMaterials {
+    pub object_id: String,
+    pub name: String,
+    pub quantity: u32,
+    // Objects with no recipe of their own, usually natural resources
+    pub raw_materials: Vec<BomLine>,
+    // Actors (and targets, e.g. a kiln) that aren't consumed. Each is needed once, unless it wears out.
+    pub tools: Vec<BomLine>,
+    // Crafted objects used up along the way
+    pub intermediates: Vec<BomLine>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BomLine {
+    pub id: String,
+    pub name: String,
+    pub count: u32,
+    // Uses actually needed, for objects with more than one use (numUses). e.g. 3 uses of a 6-use bowl of berries is 1 object.
+    pub uses: Option<f64>,
+}
+
+// How one object gets made: the inputs of the recipe step producing it, and whether each one survives the transition
+struct ProducingStep {
+    actor: i32,
+    target: i32,
+    actor_remains: bool,
+    target_remains: bool,
+}
+
+impl BillOfMaterials {
+    /// Expand an object's recipe (by name or ID) into counts of raw materials, tools and intermediates.
+    ///
+    /// Amounts are tracked in uses, so objects with numUses > 1 only count as used up once all their uses are.
+    /// An input that survives a transition with its numUses decremented is a multi-use ingredient (e.g. a berry bush),
+    /// unless it has a useChance, which makes it a tool that wears out. Tools are built from their own recipes too.
+    pub fn new(game_database: &GameDatabase, name_or_id: &str, quantity: u32) -> Result<Self> {
+        let id = game_database
+            .resolve_object_id(name_or_id)
+            .ok_or_else(|| anyhow!("No object found matching {name_or_id}"))?;
+        let root = game_database
+            .get(&id.to_string())
+            .ok_or_else(|| anyhow!("No twotech data for object {id}"))?;
+        let root_steps = recipe_steps(game_database, id);
+
+        let num_uses = |id: i32| {
+            game_database
+                .get(&id.to_string())
+                .and_then(|o| o.one_life_game_data.numUses.as_ref())
+                .map_or(1.0, |n| n.numUses.max(1) as f64)
+        };
+        // Tools only wear out if they have a chance of losing a use each time they're used
+        let wear_chance = |id: i32| {
+            game_database
+                .get(&id.to_string())
+                .and_then(|o| o.one_life_game_data.numUses.as_ref())
+                .filter(|n| n.numUses > 1)
+                .and_then(|n| n.useChance)
+                .filter(|&chance| chance > 0.0)
+                .map(f64::from)
+        };
+        let depth = |id: i32| game_database.get(&id.to_string()).and_then(|o| o.twotech_data.depth).unwrap_or(0);
+
+        // Uses of each object consumed, uses each tool loses, and how many of each object have been crafted so far
+        let mut consumed_uses = HashMap::<i32, f64>::from([(id, quantity as f64 * num_uses(id))]);
+        let mut tool_uses = HashMap::<i32, f64>::new();
+        let mut crafted = HashMap::<i32, u32>::new();
+        let mut producing_steps = HashMap::<i32, Option<ProducingStep>>::new();
+        let tool_count = |tool: i32, uses: f64| match wear_chance(tool) {
+            Some(_) => ((uses / num_uses(tool)).ceil() as u32).max(1),
+            None => 1,
+        };
+
+        let mut settled = false;
+        for _ in 0..MAX_EXPANSIONS {
+            // Objects needed in total: what gets consumed, plus enough of each tool
+            let mut needed = consumed_uses
+                .iter()
+                .map(|(&id, &uses)| (id, (uses / num_uses(id)).ceil() as u32))
+                .collect::<HashMap<_, _>>();
+            for (&tool, &uses) in &tool_uses {
+                *needed.entry(tool).or_default() += tool_count(tool, uses);
+            }
+            // Craft the deepest outstanding object first, so most of the demand for its inputs is known before they're crafted
+            let next = needed
+                .iter()
+                .filter(|(&id, &count)| count > crafted.get(&id).copied().unwrap_or(0))
+                .filter(|(&id, _)| {
+                    producing_steps
+                        .entry(id)
+                        .or_insert_with(|| producing_step(game_database, &root_steps, id))
+                        .is_some()
+                })
+                .max_by_key(|(&id, _)| (depth(id), id))
+                .map(|(&id, &count)| (id, count));
+            let Some((product, count)) = next else {
+                settled = true;
+                break;
+            };
+            let crafts = count - crafted.get(&product).copied().unwrap_or(0);
+            crafted.insert(product, count);
+            let Some(step) = &producing_steps[&product] else {
+                continue;
+            };
+            for (input, remains) in [(step.actor, step.actor_remains), (step.target, step.target_remains)] {
+                if input <= 0 || input == product {
+                    continue;
+                }
+                let uses = if remains && num_uses(input) > 1.0 {
+                    // One use per craft, or only a chance of one for a tool that wears out
+                    crafts as f64 * wear_chance(input).unwrap_or(1.0)
+                } else if remains {
+                    0.0
+                } else {
+                    crafts as f64 * num_uses(input)
+                };
+                if remains && (num_uses(input) <= 1.0 || wear_chance(input).is_some()) {
+                    *tool_uses.entry(input).or_default() += uses;
+                } else {
+                    *consumed_uses.entry(input).or_default() += uses;
+                }
+            }
+        }
+
+        // Partial totals would look like a valid bill, so don't return any
+        if !settled {
+            return Err(anyhow!(
+                "The recipe for {} never settles after {MAX_EXPANSIONS} crafts, probably because it feeds back into its own inputs",
+                root.twotech_data.name
+            ));
+        }
+
+        let line = |id: i32, count: u32, uses: f64| BomLine {
+            id: id.to_string(),
+            name: game_database.get(&id.to_string()).map_or(String::new(), |o| o.twotech_data.name.clone()),
+            count,
+            uses: Some(uses).filter(|_| num_uses(id) > 1.0),
+        };
+        let mut raw_materials = BTreeMap::new();
+        let mut intermediates = BTreeMap::new();
+        for (&input, &uses) in consumed_uses.iter().filter(|(&input, &uses)| input != id && uses > 0.0) {
+            let count = (uses / num_uses(input)).ceil() as u32;
+            match producing_steps.get(&input) {
+                Some(Some(_)) => intermediates.insert(input, line(input, count, uses)),
+                _ => raw_materials.insert(input, line(input, count, uses)),
+            };
+        }
+        let tools = tool_uses
+            .iter()
+            .map(|(&tool, &uses)| (tool, line(tool, tool_count(tool, uses), uses)))
+            .collect::<BTreeMap<_, _>>();
+
+        Ok(BillOfMaterials {
+            object_id: id.to_string(),
+            name: root.twotech_data.name.clone(),
+            quantity,
+            raw_materials: raw_materials.into_values().collect(),
+            tools: tools.into_values().collect(),
+            intermediates: intermediates.into_values().collect(),
+        })
+    }
+}
+
+impl ToString for BillOfMaterials {
+    fn to_string(&self) -> String {
+        let mut output = vec![format!("Bill of materials for {} x {} ({})", self.quantity, self.name, self.object_id)];
+        for (heading, lines) in [("Raw materials", &self.raw_materials), ("Tools", &self.tools), ("Intermediates", &self.intermediates)] {
+            output.push(format!("{heading}:"));
+            if lines.is_empty() {
+                output.push("  (none)".to_string());
+            }
+            for line in lines {
+                let uses = line.uses.map_or(String::new(), |uses| format!(" ({uses} uses)"));
+                output.push(format!("  {:>5}  {} ({}){uses}", line.count, line.name, line.id));
+            }
+        }
+        output.join("\n")
+    }
+}
+
+fn recipe_steps(game_database: &GameDatabase, id: i32) -> Vec<RecipeStep> {
+    game_database
+        .get(&id.to_string())
+        .and_then(|o| o.twotech_data.recipe.as_ref())
+        .and_then(|recipe| recipe.steps.clone())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .collect()
+}
+
+/// The recipe step that makes the object, preferring the one in the root object's recipe so the whole bill
+/// follows a single recipe tree. None for objects that aren't made by any step (raw materials).
+///
+/// Some twotech recipes only list `ingredients` without steps (e.g. a gooseberry picked from a bush),
+/// in which case a OneLifeData7 transition making the object is used, preferring one a player does over a decay.
+fn producing_step(game_database: &GameDatabase, root_steps: &[RecipeStep], id: i32) -> Option<ProducingStep> {
+    let is_producing = |step: &&RecipeStep| step.id.as_deref() == Some(id.to_string().as_str());
+    let step = root_steps
+        .iter()
+        .find(is_producing)
+        .cloned()
+        .or_else(|| recipe_steps(game_database, id).iter().find(is_producing).cloned());
+    let Some(step) = step else {
+        // Natural objects have no recipe at all, and shouldn't be expanded into whatever else happens to produce them
+        game_database.get(&id.to_string())?.twotech_data.recipe.as_ref()?;
+        let transition = game_database
+            .transitions
+            .transitions_producing(id)
+            .filter(|t| t.actorID != id && t.targetID != id)
+            .min_by_key(|t| t.is_decay())?;
+        return Some(ProducingStep {
+            actor: transition.actorID,
+            target: transition.targetID,
+            actor_remains: transition.is_tool(),
+            target_remains: transition.target_remains(),
+        });
+    };
+    // An empty actor is either the bare hand or time passing
+    let actor = step.actorID.as_deref().and_then(|a| a.parse().ok()).unwrap_or(if step.hand.unwrap_or(false) { 0 } else { -1 });
+    let target = step.targetID.as_deref().and_then(|t| t.parse().ok()).unwrap_or(-1);
+    let transition = game_database
+        .transitions
+        .transitions_with_actor(actor)
+        .find(|t| t.targetID == target && (t.newActorID == id || t.newTargetID == id));
+    Some(match transition {
+        Some(transition) => ProducingStep {
+            actor,
+            target,
+            actor_remains: transition.is_tool(),
+            target_remains: transition.target_remains(),
+        },
+        // Without the OneLifeData7 transition, assume both inputs are used up
+        None => ProducingStep {
+            actor,
+            target,
+            actor_remains: false,
+            target_remains: false,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_fixtures::game_database;
+
+    #[test]
+    fn recipe_that_feeds_back_into_itself_is_an_error() {
+        // A is made from B, and B from A, so every A crafted needs another A
+        let game_database = game_database(
+            &[
+                json!({"id": "1", "name": "A", "recipe": {"steps": [[{"id": "1", "hand": true, "targetID": "2"}]]}}),
+                json!({"id": "2", "name": "B", "recipe": {"steps": [[{"id": "2", "hand": true, "targetID": "1"}]]}}),
+            ],
+            &[],
+        );
+        let error = BillOfMaterials::new(&game_database, "A", 1).unwrap_err();
+        assert!(error.to_string().contains("never settles"), "{error}");
+    }
+
+    #[test]
+    fn tool_that_wears_out() {
+        // Each bowl takes a log, and has a 1 in 2 chance of using up one of the knife's 10 uses
+        let game_database = game_database(
+            &[
+                json!({"id": "10", "name": "Bowl", "recipe": {"steps": [[{"id": "10", "actorID": "11", "targetID": "12"}]]}}),
+                json!({"id": "11", "name": "Knife", "onelife": {"numUses": {"numUses": 10, "useChance": 0.5}}}),
+                json!({"id": "12", "name": "Log"}),
+            ],
+            &[("11_12.txt", "11 10")],
+        );
+        let bill_of_materials = BillOfMaterials::new(&game_database, "Bowl", 40).unwrap();
+        let counts = |lines: &[BomLine]| lines.iter().map(|line| (line.name.clone(), line.count, line.uses)).collect::<Vec<_>>();
+        assert_eq!(counts(&bill_of_materials.raw_materials), [("Log".to_string(), 40, None)]);
+        assert_eq!(counts(&bill_of_materials.tools), [("Knife".to_string(), 2, Some(20.0))]);
+        assert!(bill_of_materials.intermediates.is_empty());
+    }
+}
@@ -0,0 +1,377 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::game_database::GameDatabase;
+use crate::twotech_object::{DecayTime, TransitionTowardData};
+
+/// Everything that can be made from a set of objects on hand, without bringing in anything else
+#[derive(Clone, Debug, Serialize)]
+pub struct ReachableObjects {
+    pub starting_ids: Vec<String>,
+    // Whether natural objects (mapChance > 0) were treated as free inputs
+    pub natural_inputs: bool,
+    pub reached: Vec<ReachedObject>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReachedObject {
+    pub id: String,
+    pub name: String,
+    // Fewest rounds of transitions needed to make it, counting transitions that can happen side by side as one round
+    pub steps: usize,
+    // The transition that first made it. No actor is the bare hand (or time passing, for a timed transition), and no target is the ground.
+    pub actor_id: Option<String>,
+    pub target_id: Option<String>,
+    pub timed: bool,
+    pub decay: Option<DecayTime>,
+}
+
+impl ReachableObjects {
+    /// Follow twotech's transitions outward from the starting objects (by name or ID) until nothing new can be made.
+    ///
+    /// A transition can happen once its actor (or bare hand) and target (or the ground) are both available,
+    /// and timed transitions (decay) need nothing but the object itself. Both results of a transition become available.
+    /// Transitions are found in the available objects' transitionsAway and transitionsTimed, and in every object's
+    /// transitionsToward, which also lists transitions twotech only knows by a category input (e.g. "@ Any Berry Bowl").
+    /// With `max_steps`, only that many rounds of transitions are followed.
+    pub fn new(game_database: &GameDatabase, starting_objects: &[String], max_steps: Option<usize>, natural_inputs: bool) -> Result<Self> {
+        let starting_ids = starting_objects
+            .iter()
+            .map(|name_or_id| {
+                game_database
+                    .resolve_object_id(name_or_id)
+                    .map(|id| id.to_string())
+                    .ok_or_else(|| anyhow!("No object found matching {name_or_id}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut available = starting_ids.iter().cloned().collect::<BTreeSet<_>>();
+        if natural_inputs {
+            available.extend(
+                game_database
+                    .objects
+                    .iter()
+                    .filter(|(_, o)| o.one_life_game_data.mapChance.as_ref().is_some_and(|m| m.mapChance > 0.0))
+                    .map(|(id, _)| id.clone()),
+            );
+        }
+        // A category (as an input) is available if any of its members are
+        let is_available = |available: &BTreeSet<String>, id: &Option<String>| match id {
+            None => true,
+            Some(id) => {
+                available.contains(id)
+                    || id.parse().is_ok_and(|id| {
+                        game_database.categories.contains_key(&id)
+                            && game_database.expand_category(id).iter().any(|member| available.contains(&member.id.to_string()))
+                    })
+            }
+        };
+
+        // transitionsToward are listed on the product, so index them by input to find them from the frontier.
+        // A category input is indexed under each of its members.
+        let mut toward_by_input = HashMap::<String, Vec<&TransitionTowardData>>::new();
+        for transition in game_database.objects.values().flat_map(|o| o.twotech_data.transitionsToward.iter().flatten()) {
+            for input in [&transition.actorID, &transition.targetID].into_iter().flatten() {
+                let members = match input.parse() {
+                    Ok(id) if game_database.categories.contains_key(&id) => {
+                        game_database.expand_category(id).iter().map(|member| member.id.to_string()).collect()
+                    }
+                    _ => vec![input.clone()],
+                };
+                for member in members {
+                    toward_by_input.entry(member).or_default().push(transition);
+                }
+            }
+        }
+
+        let mut reached = BTreeMap::<String, ReachedObject>::new();
+        let mut frontier = available.iter().cloned().collect::<Vec<_>>();
+        let mut steps = 0;
+        while !frontier.is_empty() && max_steps.is_none_or(|max_steps| steps < max_steps) {
+            steps += 1;
+            // Products of this round only become inputs in the next one, so each object gets its fewest rounds
+            let mut made = BTreeMap::<String, ReachedObject>::new();
+            for id in &frontier {
+                let Some(shared_obj) = game_database.get(id) else {
+                    continue;
+                };
+                let twotech = &shared_obj.twotech_data;
+                let transitions = twotech
+                    .transitionsAway
+                    .iter()
+                    .flatten()
+                    .filter(|t| is_available(&available, &t.actorID) && is_available(&available, &t.targetID))
+                    .map(|t| (t.actorID.clone(), t.targetID.clone(), false, t.decay.clone(), [&t.newActorID, &t.newTargetID]));
+                let decays = twotech
+                    .transitionsTimed
+                    .iter()
+                    .flatten()
+                    .map(|t| (None, t.targetID.clone(), true, t.decay.clone(), [&None, &t.newTargetID]));
+                let toward = toward_by_input
+                    .get(id)
+                    .into_iter()
+                    .flatten()
+                    .filter(|t| is_available(&available, &t.actorID) && is_available(&available, &t.targetID))
+                    .map(|t| (t.actorID.clone(), t.targetID.clone(), t.actorID.is_none() && t.decay.is_some(), t.decay.clone(), [&t.newActorID, &t.newTargetID]));
+                for (actor_id, target_id, timed, decay, products) in transitions.chain(decays).chain(toward) {
+                    for product_id in products.into_iter().flatten() {
+                        if available.contains(product_id) || made.contains_key(product_id) {
+                            continue;
+                        }
+                        made.insert(product_id.clone(), ReachedObject {
+                            id: product_id.clone(),
+                            name: game_database.get(product_id).map_or(String::new(), |o| o.twotech_data.name.clone()),
+                            steps,
+                            actor_id: actor_id.clone(),
+                            target_id: target_id.clone(),
+                            timed,
+                            decay: decay.clone(),
+                        });
+                    }
+                }
+            }
+            frontier = made.keys().cloned().collect();
+            available.extend(made.keys().cloned());
+            reached.append(&mut made);
+        }
+
+        let mut reached = reached.into_values().collect::<Vec<_>>();
+        reached.sort_by_key(|reached| (reached.steps, reached.id.parse::<i32>().ok()));
+        Ok(ReachableObjects { starting_ids, natural_inputs, reached })
+    }
+}
+
+/// What `TransitionPath::find` minimizes
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PathCost {
+    // Fewest transitions
+    #[default]
+    Steps,
+    // Least time spent waiting on decays, then fewest transitions
+    Wait,
+}
+
+impl FromStr for PathCost {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "steps" | "shortest" => Ok(PathCost::Steps),
+            "wait" | "time" | "cheapest" => Ok(PathCost::Wait),
+            _ => Err(anyhow!("Unknown path cost {s} (expected steps or wait)"))
+        }
+    }
+}
+
+/// A sequence of transitions turning one object into another
+#[derive(Clone, Debug, Serialize)]
+pub struct TransitionPath {
+    pub from_id: String,
+    pub to_id: String,
+    pub steps: Vec<PathStep>,
+    // Total time spent waiting on timed transitions
+    pub wait_seconds: u64,
+}
+
+/// One transition along a path. The object being followed is either the actor or the target, and becomes one of the results.
+#[derive(Clone, Debug, Serialize)]
+pub struct PathStep {
+    // No actor is the bare hand (or time passing, for a timed transition), and no target is the ground
+    pub actor_id: Option<String>,
+    pub target_id: Option<String>,
+    pub new_actor_id: Option<String>,
+    pub new_target_id: Option<String>,
+    pub timed: bool,
+    pub decay: Option<DecayTime>,
+    // How long a timed transition takes, None for player actions
+    pub wait_seconds: Option<u64>,
+}
+
+impl TransitionPath {
+    /// Search twotech's transitions for the cheapest way to turn one object (by name or ID) into another.
+    ///
+    /// Each step is a transition the current object takes part in (using it, using something on it, or waiting for it
+    /// to decay), moving on to either of its results. Whatever else the transition needs is assumed to be at hand.
+    /// Ok(None) if there's no way from one to the other.
+    pub fn find(game_database: &GameDatabase, from: &str, to: &str, cost: PathCost) -> Result<Option<Self>> {
+        let resolve = |name_or_id: &str| {
+            game_database
+                .resolve_object_id(name_or_id)
+                .map(|id| id.to_string())
+                .ok_or_else(|| anyhow!("No object found matching {name_or_id}"))
+        };
+        let (from_id, to_id) = (resolve(from)?, resolve(to)?);
+
+        // Dijkstra's over objects, costed by (wait, steps) or (steps, wait)
+        let step_cost = |wait: u64, steps: usize| match cost {
+            PathCost::Steps => (steps as u64, wait),
+            PathCost::Wait => (wait, steps as u64),
+        };
+        let mut best = HashMap::<String, ((u64, u64), Option<(String, PathStep)>)>::from([(from_id.clone(), ((0, 0), None))]);
+        let mut queue = BinaryHeap::from([Reverse(((0, 0), 0, 0, from_id.clone()))]);
+        while let Some(Reverse((current_cost, wait, steps, id))) = queue.pop() {
+            if id == to_id {
+                break;
+            }
+            if best.get(&id).is_some_and(|(best_cost, _)| *best_cost < current_cost) {
+                continue;
+            }
+            for step in next_steps(game_database, &id) {
+                let step_wait = wait + step.wait_seconds.unwrap_or(0);
+                let next_cost = step_cost(step_wait, steps + 1);
+                for next_id in [&step.new_actor_id, &step.new_target_id].into_iter().flatten() {
+                    if *next_id == id || best.get(next_id).is_some_and(|(best_cost, _)| *best_cost <= next_cost) {
+                        continue;
+                    }
+                    best.insert(next_id.clone(), (next_cost, Some((id.clone(), step.clone()))));
+                    queue.push(Reverse((next_cost, step_wait, steps + 1, next_id.clone())));
+                }
+            }
+        }
+
+        if !best.contains_key(&to_id) {
+            return Ok(None);
+        }
+        let mut steps = Vec::new();
+        let mut current = &to_id;
+        while let Some((_, Some((previous, step)))) = best.get(current) {
+            steps.push(step.clone());
+            current = previous;
+        }
+        steps.reverse();
+        let wait_seconds = steps.iter().filter_map(|step| step.wait_seconds).sum();
+        Ok(Some(TransitionPath { from_id, to_id, steps, wait_seconds }))
+    }
+}
+
+// Every transition the object takes part in: as actor or target, or by decaying
+fn next_steps(game_database: &GameDatabase, id: &str) -> Vec<PathStep> {
+    let Some(shared_obj) = game_database.get(id) else {
+        return Vec::new();
+    };
+    let twotech = &shared_obj.twotech_data;
+    let transitions = twotech.transitionsAway.iter().flatten().map(|t| PathStep {
+        actor_id: t.actorID.clone(),
+        target_id: t.targetID.clone(),
+        new_actor_id: t.newActorID.clone(),
+        new_target_id: t.newTargetID.clone(),
+        timed: false,
+        decay: t.decay.clone(),
+        wait_seconds: None,
+    });
+    let decays = twotech.transitionsTimed.iter().flatten().map(|t| PathStep {
+        actor_id: None,
+        target_id: t.targetID.clone(),
+        new_actor_id: None,
+        new_target_id: t.newTargetID.clone(),
+        timed: true,
+        decay: t.decay.clone(),
+        wait_seconds: Some(t.decay.as_ref().and_then(DecayTime::seconds).unwrap_or(0)),
+    });
+    transitions.chain(decays).collect()
+}
+
+/// The time spent waiting on things to decay while following an object's twotech recipe
+#[derive(Clone, Debug, Serialize)]
+pub struct RecipeWait {
+    pub object_id: String,
+    pub name: String,
+    pub waits: Vec<RecipeWaitStep>,
+    // Sum of every wait with a set time. Epoch decays aren't counted.
+    pub total_seconds: u64,
+}
+
+/// A recipe step where an object is left alone until it decays into the next one
+#[derive(Clone, Debug, Serialize)]
+pub struct RecipeWaitStep {
+    pub target_id: String,
+    pub product_id: String,
+    pub decay: DecayTime,
+}
+
+impl RecipeWait {
+    pub fn new(game_database: &GameDatabase, name_or_id: &str) -> Result<Self> {
+        let id = game_database
+            .resolve_object_id(name_or_id)
+            .ok_or_else(|| anyhow!("No object found matching {name_or_id}"))?
+            .to_string();
+        let shared_obj = game_database.get(&id).ok_or_else(|| anyhow!("No twotech data for object {id}"))?;
+        let steps = shared_obj.twotech_data.recipe.as_ref().and_then(|recipe| recipe.steps.as_ref());
+        let waits = steps
+            .into_iter()
+            .flatten()
+            .flatten()
+            // Steps with neither an actor nor the bare hand are time passing
+            .filter(|step| step.actorID.is_none() && !step.hand.unwrap_or(false))
+            .filter_map(|step| {
+                let (target_id, product_id) = (step.targetID.as_ref()?, step.id.as_ref()?);
+                let target = &game_database.get(target_id)?.twotech_data;
+                let decay = target
+                    .transitionsTimed
+                    .iter()
+                    .flatten()
+                    .find(|t| t.newTargetID.as_ref() == Some(product_id))
+                    .and_then(|t| t.decay.clone())
+                    .unwrap_or_else(|| target.decay_time());
+                Some(RecipeWaitStep { target_id: target_id.clone(), product_id: product_id.clone(), decay })
+            })
+            .collect::<Vec<_>>();
+        let total_seconds = waits.iter().filter_map(|wait| wait.decay.seconds()).sum();
+        Ok(RecipeWait { object_id: id, name: shared_obj.twotech_data.name.clone(), waits, total_seconds })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::one_life_data_category::CategoryData;
+    use crate::test_fixtures::game_database;
+
+    fn reached(game_database: &GameDatabase, starting_objects: &[&str]) -> Vec<(String, usize)> {
+        let starting_objects = starting_objects.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        ReachableObjects::new(game_database, &starting_objects, None, false)
+            .unwrap()
+            .reached
+            .into_iter()
+            .map(|object| (object.id, object.steps))
+            .collect()
+    }
+
+    #[test]
+    fn transitions_away_and_decays_are_followed() {
+        let game_database = game_database(
+            &[
+                json!({"id": "1", "name": "Stone", "transitionsAway": [{"actorID": "1", "targetID": "2", "newTargetID": "3"}]}),
+                json!({"id": "2", "name": "Log"}),
+                json!({"id": "3", "name": "Burning Log", "transitionsTimed": [{"targetID": "3", "newTargetID": "4", "decay": "30s"}]}),
+                json!({"id": "4", "name": "Ashes"}),
+            ],
+            &[],
+        );
+        assert_eq!(reached(&game_database, &["1", "2"]), [("3".to_string(), 1), ("4".to_string(), 2)]);
+    }
+
+    #[test]
+    fn category_transitions_toward_a_product_are_followed() {
+        // Only the bowl of berries lists the transition, under the category rather than the berry.
+        let mut game_database = game_database(
+            &[
+                json!({"id": "1", "name": "Berry"}),
+                json!({"id": "2", "name": "Bowl"}),
+                json!({"id": "3", "name": "Bowl of Berries", "transitionsToward": [{"actorID": "5", "targetID": "2", "newTargetID": "3"}]}),
+                json!({"id": "5", "name": "@ Any Berry"}),
+            ],
+            &[],
+        );
+        let category = "parentID=5\nnumObjects=1\n1".parse::<CategoryData>().unwrap();
+        game_database.categories.insert(category.parentID, category);
+        assert_eq!(reached(&game_database, &["1", "2"]), [("3".to_string(), 1)]);
+        assert_eq!(reached(&game_database, &["1"]), []);
+    }
+}
@@ -0,0 +1,213 @@
+use serde::Serialize;
+
+use crate::game_database::GameDatabase;
+use crate::one_life_data_object::BiomeId;
+use crate::twotech_object::{MoveType, TransitionAwayData, TwoTechObject};
+use crate::SharedGameObject;
+
+// Words in the name of what a creature turns into that say whether it was killed or tamed. Matched against whole
+// words, so "Untied" or "Deadly" don't count.
+const KILLED_WORDS: [&str; 5] = ["dead", "wounded", "shot", "killed", "carcass"];
+const TAMED_WORDS: [&str; 6] = ["domestic", "tame", "penned", "tied", "lassoed", "fed"];
+
+/// How a creature moves, how dangerous it is, and how to deal with it
+#[derive(Clone, Debug, Serialize)]
+pub struct Creature {
+    pub id: String,
+    pub name: String,
+    pub move_type: Option<MoveType>,
+    pub move_distance: Option<i32>,
+    // How close it has to get to kill, 0 if it's harmless
+    pub deadly_distance: i32,
+    pub speed_mult: Option<f64>,
+    pub biomes: Vec<BiomeId>,
+    // Transitions on the creature that leave it domesticated (or tied up, penned, fed...)
+    pub tame: Vec<CreatureTransition>,
+    // Transitions on the creature that leave it dead or wounded
+    pub kill: Vec<CreatureTransition>,
+    // Transitions that leave the creature unable to move or kill, but whose result isn't named as killed or tamed
+    // (e.g. trapped, put in a basket or harnessed)
+    pub other: Vec<CreatureTransition>,
+    // Transitions on whatever killing it leaves behind
+    pub butcher: Vec<CreatureTransition>,
+}
+
+/// A transition with the creature (or its remains) as the target. No actor is the bare hand.
+#[derive(Clone, Debug, Serialize)]
+pub struct CreatureTransition {
+    pub actor_id: Option<String>,
+    pub target_id: Option<String>,
+    pub new_actor_id: Option<String>,
+    pub new_target_id: Option<String>,
+}
+
+impl CreatureTransition {
+    fn new(transition: &TransitionAwayData) -> Self {
+        CreatureTransition {
+            actor_id: transition.actorID.clone(),
+            target_id: transition.targetID.clone(),
+            new_actor_id: transition.newActorID.clone(),
+            new_target_id: transition.newTargetID.clone(),
+        }
+    }
+}
+
+impl Creature {
+    /// None if the object neither moves on its own nor is deadly
+    pub fn new(game_database: &GameDatabase, shared_obj: &SharedGameObject) -> Option<Self> {
+        let twotech = &shared_obj.twotech_data;
+        if !is_creature(twotech) {
+            return None;
+        }
+        let deadly_distance = twotech.deadlyDistance.unwrap_or(0);
+        let transitions_on = |target: &SharedGameObject| {
+            target
+                .twotech_data
+                .transitionsAway
+                .iter()
+                .flatten()
+                .filter(|t| t.targetID.as_ref() == Some(&target.twotech_data.id))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        let mut tame = Vec::new();
+        let mut kill = Vec::new();
+        let mut other = Vec::new();
+        let mut remains = Vec::new();
+        // Creatures wandering off or changing on their own aren't something the player did to them
+        for transition in transitions_on(shared_obj).into_iter().filter(|t| t.decay.is_none()) {
+            let Some(result) = transition.newTargetID.as_deref().and_then(|id| game_database.get(id)) else {
+                continue;
+            };
+            let words = result.twotech_data.name.to_lowercase().split(|c: char| !c.is_alphanumeric()).map(str::to_string).collect::<Vec<_>>();
+            let has_any = |list: &[&str]| list.iter().any(|word| words.iter().any(|w| w == word));
+            if has_any(&KILLED_WORDS) {
+                remains.push(result.twotech_data.id.clone());
+                kill.push(CreatureTransition::new(&transition));
+            } else if has_any(&TAMED_WORDS) {
+                tame.push(CreatureTransition::new(&transition));
+            } else if !is_creature(&result.twotech_data) {
+                // It no longer moves or kills, so something was done to it, but the name doesn't say what
+                other.push(CreatureTransition::new(&transition));
+            }
+        }
+        remains.sort();
+        remains.dedup();
+        // Butchering is using a tool (an actor that isn't used up, like a knife) on the remains
+        let butcher = remains
+            .iter()
+            .filter_map(|id| game_database.get(id))
+            .flat_map(transitions_on)
+            .filter(|t| t.decay.is_none())
+            .filter(|t| t.tool.unwrap_or(false) || (t.actorID.is_some() && t.actorID == t.newActorID))
+            .map(|transition| CreatureTransition::new(&transition))
+            .collect();
+
+        Some(Creature {
+            id: twotech.id.clone(),
+            name: twotech.name.clone(),
+            move_type: twotech.moveType.clone(),
+            move_distance: twotech.moveDistance,
+            deadly_distance,
+            speed_mult: twotech.speedMult,
+            biomes: shared_obj.natural_biomes().into_iter().map(|(biome, _)| biome).collect(),
+            tame,
+            kill,
+            other,
+            butcher,
+        })
+    }
+}
+
+// Moves on its own or can kill
+fn is_creature(twotech: &TwoTechObject) -> bool {
+    let moves = twotech.moveType.as_ref().is_some_and(|move_type| move_type != &MoveType::None);
+    moves || twotech.deadlyDistance.unwrap_or(0) > 0
+}
+
+/// Every creature (anything that moves on its own or can kill), sorted by how far away it can kill from, most dangerous first
+pub fn creatures(game_database: &GameDatabase) -> Vec<Creature> {
+    let mut creatures = game_database
+        .objects
+        .values()
+        .filter(|shared_obj| !shared_obj.twotech_data.name.contains("removed"))
+        .filter_map(|shared_obj| Creature::new(game_database, shared_obj))
+        .collect::<Vec<_>>();
+    creatures.sort_by(|a, b| b.deadly_distance.cmp(&a.deadly_distance).then(a.name.cmp(&b.name)));
+    creatures
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_fixtures::game_database;
+
+    fn transition(actor: Option<&str>, target: &str, new_actor: Option<&str>, new_target: &str) -> serde_json::Value {
+        json!({"actorID": actor, "targetID": target, "newActorID": new_actor, "newTargetID": new_target})
+    }
+
+    fn wolf() -> Creature {
+        let game_database = game_database(
+            &[
+                json!({"id": "9", "name": "Wolf", "moveType": 1, "deadlyDistance": 2, "transitionsAway": [
+                    transition(Some("4"), "9", None, "10"),
+                    transition(Some("12"), "9", None, "13"),
+                    transition(Some("14"), "9", None, "15"),
+                    transition(None, "9", None, "16"),
+                    {"targetID": "9", "newTargetID": "15", "decay": "30s"},
+                ]}),
+                json!({"id": "10", "name": "Dead Wolf", "transitionsAway": [
+                    {"actorID": "3", "targetID": "10", "newActorID": "3", "newTargetID": "11", "tool": true},
+                    transition(Some("2"), "10", None, "10"),
+                ]}),
+                json!({"id": "2", "name": "Branch"}),
+                json!({"id": "3", "name": "Knife"}),
+                json!({"id": "4", "name": "Skewer"}),
+                json!({"id": "11", "name": "Wolf Skin"}),
+                json!({"id": "12", "name": "Rope"}),
+                json!({"id": "13", "name": "Lassoed Wolf"}),
+                json!({"id": "14", "name": "Basket"}),
+                json!({"id": "15", "name": "Wolf in Basket"}),
+                // Still on the loose, and "Untied" isn't "tied"
+                json!({"id": "16", "name": "Untied Wolf", "moveType": 1}),
+            ],
+            &[],
+        );
+        Creature::new(&game_database, game_database.get("9").unwrap()).unwrap()
+    }
+
+    fn actors(transitions: &[CreatureTransition]) -> Vec<Option<&str>> {
+        transitions.iter().map(|t| t.actor_id.as_deref()).collect()
+    }
+
+    #[test]
+    fn kill() {
+        assert_eq!(actors(&wolf().kill), [Some("4")]);
+    }
+
+    #[test]
+    fn tame() {
+        assert_eq!(actors(&wolf().tame), [Some("12")]);
+    }
+
+    #[test]
+    fn butcher_with_a_tool() {
+        let wolf = wolf();
+        assert_eq!(actors(&wolf.butcher), [Some("3")]);
+        assert_eq!(wolf.butcher[0].new_target_id.as_deref(), Some("11"));
+    }
+
+    #[test]
+    fn unnamed_results_are_other_rather_than_kills() {
+        assert_eq!(actors(&wolf().other), [Some("14")]);
+    }
+
+    #[test]
+    fn objects_that_dont_move_or_kill_arent_creatures() {
+        let game_database = game_database(&[json!({"id": "1", "name": "Stone"})], &[]);
+        assert!(Creature::new(&game_database, game_database.get("1").unwrap()).is_none());
+    }
+}
@@ -0,0 +1,528 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::SharedGameObject;
+
+/// A boolean expression over an object's fields, given with `--where`, e.g.
+/// `numSlots >= 2 and (clothing in [t,b] or insulation > 0.1) and not name ~ "Removed"`
+///
+/// Fields are looked up in the object's twotech data first and then its OneLifeData7 data, unless
+/// prefixed with `twotech.` or `onelife.`. Nested values are reached with dots (`onelife.permanent.minPickupAge`),
+/// and a nested object holding a key with the same name as the field (e.g. OneLifeData7's
+/// `numSlots: {numSlots, timeStretch}`) is unwrapped to that key's value.
+#[derive(Clone, Debug)]
+pub enum FilterExpression {
+    And(Box<FilterExpression>, Box<FilterExpression>),
+    Or(Box<FilterExpression>, Box<FilterExpression>),
+    Not(Box<FilterExpression>),
+    Compare { field: String, operator: CompareOperator, value: Literal },
+    In { field: String, values: Vec<Literal> },
+    // Inclusive on both ends, an open end is None
+    InRange { field: String, start: Option<f64>, end: Option<f64> },
+    Matches { field: String, regex: Regex },
+    // A bare field name, true if the field is present and not false, 0 or empty
+    Truthy { field: String },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompareOperator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    Number(f64),
+    // Quoted strings and bare words (e.g. the t in `clothing = t`)
+    Text(String),
+    Bool(bool),
+    Null,
+}
+
+impl FilterExpression {
+    pub fn matches(&self, shared_obj: &SharedGameObject) -> bool {
+        self.evaluate(shared_obj.as_json())
+    }
+
+    /// Evaluate against a SharedGameObject's JSON (`SharedGameObject::as_json`)
+    pub fn evaluate(&self, object: &Value) -> bool {
+        match self {
+            FilterExpression::And(left, right) => left.evaluate(object) && right.evaluate(object),
+            FilterExpression::Or(left, right) => left.evaluate(object) || right.evaluate(object),
+            FilterExpression::Not(inner) => !inner.evaluate(object),
+            FilterExpression::Compare { field, operator, value } => {
+                // Lists (e.g. foodValue, biomes) match if any of their elements do
+                any_element(&lookup_field(object, field), |v| compare(v, *operator, value))
+            }
+            FilterExpression::In { field, values } => {
+                any_element(&lookup_field(object, field), |v| values.iter().any(|value| compare(v, CompareOperator::Equal, value)))
+            }
+            FilterExpression::InRange { field, start, end } => {
+                any_element(&lookup_field(object, field), |v| {
+                    as_number(v).is_some_and(|n| start.is_none_or(|start| n >= start) && end.is_none_or(|end| n <= end))
+                })
+            }
+            FilterExpression::Matches { field, regex } => {
+                any_element(&lookup_field(object, field), |v| match v {
+                    Value::String(s) => regex.is_match(s),
+                    Value::Number(n) => regex.is_match(&n.to_string()),
+                    Value::Bool(b) => regex.is_match(&b.to_string()),
+                    _ => false,
+                })
+            }
+            FilterExpression::Truthy { field } => match &lookup_field(object, field) {
+                Value::Null => false,
+                Value::Bool(b) => *b,
+                Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+                Value::String(s) => !s.is_empty(),
+                Value::Array(a) => !a.is_empty(),
+                Value::Object(o) => !o.is_empty(),
+            },
+        }
+    }
+}
+
+/// Find a field in a SharedGameObject's JSON (`SharedGameObject::as_json`). Missing fields are Null.
+/// Stepping into a list picks the field out of each element, e.g. `onelife.sounds.eatingSound.id` is the list of eating sound IDs.
+pub fn lookup_field(object: &Value, field: &str) -> Value {
+    let (sections, path) = match field.split_once('.') {
+        Some(("twotech", path)) => (vec!["twotech_data"], path),
+        Some(("onelife", path)) => (vec!["one_life_game_data"], path),
+        _ => (vec!["twotech_data", "one_life_game_data"], field),
+    };
+    let field_name = path.rsplit('.').next().unwrap_or(path);
+    sections
+        .iter()
+        .map(|section| {
+            let value = path.split('.').fold(object[section].clone(), |value, segment| match value {
+                Value::Array(elements) => Value::Array(elements.into_iter().map(|element| element[segment].clone()).collect()),
+                value => value[segment].clone(),
+            });
+            match value {
+                Value::Object(mut nested) if nested.contains_key(field_name) => nested.remove(field_name).unwrap_or_default(),
+                value => value,
+            }
+        })
+        .find(|value| !value.is_null())
+        .unwrap_or(Value::Null)
+}
+
+fn any_element(value: &Value, predicate: impl Fn(&Value) -> bool) -> bool {
+    match value {
+        Value::Array(elements) => elements.iter().any(predicate),
+        _ => predicate(value),
+    }
+}
+
+// Numbers, and strings that hold a number (twotech stores IDs as strings)
+pub(crate) fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn compare(value: &Value, operator: CompareOperator, literal: &Literal) -> bool {
+    let ordering = match (literal, value) {
+        (Literal::Null, _) => {
+            return match operator {
+                CompareOperator::Equal => value.is_null(),
+                CompareOperator::NotEqual => !value.is_null(),
+                _ => false,
+            };
+        }
+        // Missing values only ever match `!= something`
+        (_, Value::Null) => return operator == CompareOperator::NotEqual,
+        (Literal::Bool(b), Value::Bool(v)) => Some(v.cmp(b)),
+        (Literal::Number(n), _) => as_number(value).and_then(|v| v.partial_cmp(n)),
+        (Literal::Text(text), Value::String(s)) => Some(s.to_lowercase().cmp(&text.to_lowercase())),
+        (Literal::Text(text), Value::Bool(v)) => text.parse::<bool>().ok().map(|b| v.cmp(&b)),
+        (Literal::Text(text), Value::Number(_)) => text.parse::<f64>().ok().and_then(|n| as_number(value)?.partial_cmp(&n)),
+        _ => None,
+    };
+    match ordering {
+        Some(ordering) => match operator {
+            CompareOperator::Equal => ordering == Ordering::Equal,
+            CompareOperator::NotEqual => ordering != Ordering::Equal,
+            CompareOperator::Less => ordering == Ordering::Less,
+            CompareOperator::LessOrEqual => ordering != Ordering::Greater,
+            CompareOperator::Greater => ordering == Ordering::Greater,
+            CompareOperator::GreaterOrEqual => ordering != Ordering::Less,
+        },
+        // Values that can't be compared (e.g. a list against a string) are never equal
+        None => operator == CompareOperator::NotEqual,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Number(f64),
+    Text(String),
+    Operator(&'static str),
+}
+
+// Longest first, so "<=" isn't read as "<" followed by "="
+const OPERATORS: [&str; 16] = ["==", "!=", "<=", ">=", "&&", "||", "..", "=", "<", ">", "~", "!", "(", ")", "[", "]"];
+
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>> {
+    let chars = s.char_indices().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (position, c) = chars[i];
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+        if c.is_whitespace() || c == ',' {
+            i += 1;
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some(&(_, '\\')) => {
+                        text.extend(chars.get(i + 1).map(|&(_, c)| c));
+                        i += 2;
+                    }
+                    Some(&(_, end)) if end == c => break,
+                    Some(&(_, other)) => {
+                        text.push(other);
+                        i += 1;
+                    }
+                    None => return Err(anyhow!("Unterminated string starting at position {position}")),
+                }
+            }
+            i += 1;
+            tokens.push((position, Token::Text(text)));
+        } else if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) {
+            i += 1;
+            while i < chars.len() {
+                let (_, c) = chars[i];
+                let next = chars.get(i + 1).map(|&(_, c)| c);
+                // A '.' is only part of the number if a digit follows, so "1..4" is a range
+                if c.is_ascii_digit() || (c == '.' && next.is_some_and(|n| n.is_ascii_digit())) {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            let end = chars.get(i).map_or(s.len(), |&(end, _)| end);
+            let number = s[position..end].parse().map_err(|e| anyhow!("Invalid number at position {position}: {e}"))?;
+            tokens.push((position, Token::Number(number)));
+        } else if c.is_alphabetic() || c == '_' || c == '@' {
+            while i < chars.len() && (chars[i].1.is_alphanumeric() || matches!(chars[i].1, '_' | '@' | '.')) {
+                // Stop before a range operator, e.g. "a..b"
+                if chars[i].1 == '.' && chars.get(i + 1).is_some_and(|&(_, c)| c == '.') {
+                    break;
+                }
+                i += 1;
+            }
+            let end = chars.get(i).map_or(s.len(), |&(end, _)| end);
+            tokens.push((position, Token::Word(s[position..end].to_string())));
+        } else {
+            let operator = OPERATORS
+                .iter()
+                .find(|operator| s[position..].starts_with(*operator))
+                .ok_or_else(|| anyhow!("Unexpected character '{c}' at position {position}"))?;
+            i += operator.chars().count();
+            tokens.push((position, Token::Operator(operator)));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    end_position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.index).map_or(self.end_position, |&(position, _)| position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).map(|(_, token)| token.clone());
+        self.index += 1;
+        token
+    }
+
+    // Keywords are case-insensitive, and the symbol forms (&&, ||, !) are accepted too
+    fn next_is(&mut self, keyword: &str, symbol: &str) -> bool {
+        let found = match self.peek() {
+            Some(Token::Word(word)) => word.eq_ignore_ascii_case(keyword),
+            Some(Token::Operator(operator)) => *operator == symbol,
+            _ => false,
+        };
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn expect_operator(&mut self, expected: &str) -> Result<()> {
+        let position = self.position();
+        match self.next() {
+            Some(Token::Operator(operator)) if operator == expected => Ok(()),
+            _ => Err(anyhow!("Expected '{expected}' at position {position}")),
+        }
+    }
+
+    fn or_expression(&mut self) -> Result<FilterExpression> {
+        let mut expression = self.and_expression()?;
+        while self.next_is("or", "||") {
+            expression = FilterExpression::Or(Box::new(expression), Box::new(self.and_expression()?));
+        }
+        Ok(expression)
+    }
+
+    fn and_expression(&mut self) -> Result<FilterExpression> {
+        let mut expression = self.unary_expression()?;
+        while self.next_is("and", "&&") {
+            expression = FilterExpression::And(Box::new(expression), Box::new(self.unary_expression()?));
+        }
+        Ok(expression)
+    }
+
+    fn unary_expression(&mut self) -> Result<FilterExpression> {
+        if self.next_is("not", "!") {
+            return Ok(FilterExpression::Not(Box::new(self.unary_expression()?)));
+        }
+        if self.peek() == Some(&Token::Operator("(")) {
+            self.index += 1;
+            let expression = self.or_expression()?;
+            self.expect_operator(")")?;
+            return Ok(expression);
+        }
+        self.condition()
+    }
+
+    fn condition(&mut self) -> Result<FilterExpression> {
+        let position = self.position();
+        let field = match self.next() {
+            Some(Token::Word(field)) => field,
+            _ => return Err(anyhow!("Expected a field name at position {position}")),
+        };
+        let operator = match self.peek() {
+            Some(Token::Operator("=" | "==")) => CompareOperator::Equal,
+            Some(Token::Operator("!=")) => CompareOperator::NotEqual,
+            Some(Token::Operator("<")) => CompareOperator::Less,
+            Some(Token::Operator("<=")) => CompareOperator::LessOrEqual,
+            Some(Token::Operator(">")) => CompareOperator::Greater,
+            Some(Token::Operator(">=")) => CompareOperator::GreaterOrEqual,
+            Some(Token::Operator("~")) => {
+                self.index += 1;
+                let position = self.position();
+                let pattern = match self.next() {
+                    Some(Token::Text(pattern) | Token::Word(pattern)) => pattern,
+                    _ => return Err(anyhow!("Expected a regular expression at position {position}")),
+                };
+                let regex = Regex::new(&pattern).map_err(|e| anyhow!("Invalid regular expression at position {position}: {e}"))?;
+                return Ok(FilterExpression::Matches { field, regex });
+            }
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("in") => {
+                self.index += 1;
+                return self.in_condition(field);
+            }
+            _ => return Ok(FilterExpression::Truthy { field }),
+        };
+        self.index += 1;
+        let value = self.literal()?;
+        Ok(FilterExpression::Compare { field, operator, value })
+    }
+
+    // Either a list, `in [a, b, c]`, or a range, `in 1..4`, `in 2..` or `in ..0.5`
+    fn in_condition(&mut self, field: String) -> Result<FilterExpression> {
+        if self.peek() == Some(&Token::Operator("[")) {
+            self.index += 1;
+            let mut values = Vec::new();
+            while self.peek() != Some(&Token::Operator("]")) {
+                if self.peek().is_none() {
+                    return Err(anyhow!("Expected ']' at position {}", self.position()));
+                }
+                values.push(self.literal()?);
+            }
+            self.index += 1;
+            return Ok(FilterExpression::In { field, values });
+        }
+        let start = match self.peek() {
+            Some(&Token::Number(n)) => {
+                self.index += 1;
+                Some(n)
+            }
+            _ => None,
+        };
+        self.expect_operator("..")?;
+        let end = match self.peek() {
+            Some(&Token::Number(n)) => {
+                self.index += 1;
+                Some(n)
+            }
+            _ => None,
+        };
+        Ok(FilterExpression::InRange { field, start, end })
+    }
+
+    fn literal(&mut self) -> Result<Literal> {
+        let position = self.position();
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Literal::Number(n)),
+            Some(Token::Text(text)) => Ok(Literal::Text(text)),
+            Some(Token::Word(word)) => Ok(match word.to_lowercase().as_str() {
+                "true" => Literal::Bool(true),
+                "false" => Literal::Bool(false),
+                "null" => Literal::Null,
+                _ => Literal::Text(word),
+            }),
+            _ => Err(anyhow!("Expected a value at position {position}")),
+        }
+    }
+}
+
+impl FromStr for FilterExpression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            index: 0,
+            end_position: s.len(),
+        };
+        let expression = parser.or_expression()?;
+        if parser.peek().is_some() {
+            return Err(anyhow!("Unexpected input at position {}", parser.position()));
+        }
+        Ok(expression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    // The same layout as SharedGameObject::as_json, with a few fields that only one side has
+    fn object() -> Value {
+        json!({
+            "twotech_data": {
+                "id": "8",
+                "name": "Backpack",
+                "numSlots": 4,
+                "insulation": 0.02,
+                "clothing": "p",
+                "foodValue": [0, 0],
+                "craftable": true,
+            },
+            "one_life_game_data": {
+                "id": 8,
+                "name": "Backpack",
+                "numSlots": {"numSlots": 4, "timeStretch": 1.0},
+                "rValue": 0.2,
+                "permanent": {"permanent": false, "minPickupAge": 3},
+                "heldInHand": 1,
+            },
+        })
+    }
+
+    fn matches(expression: &str) -> bool {
+        expression.parse::<FilterExpression>().unwrap().evaluate(&object())
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        // (true) or ((false) and (false)), not ((true or false) and false)
+        assert!(matches("numSlots = 4 or numSlots = 1 and clothing = h"));
+        assert!(!matches("(numSlots = 4 or numSlots = 1) and clothing = h"));
+        assert!(matches("numSlots = 4 || numSlots = 1 && clothing = h"));
+    }
+
+    #[test]
+    fn not_applies_to_the_next_condition_only() {
+        assert!(!matches("not numSlots = 4"));
+        assert!(matches("not clothing = h and numSlots = 4"));
+        assert!(!matches("not (clothing = p and numSlots = 4)"));
+        assert!(matches("!!craftable"));
+    }
+
+    #[test]
+    fn comparisons() {
+        assert!(matches("numSlots >= 4 and numSlots <= 4 and numSlots > 3 and numSlots < 5"));
+        assert!(matches("numSlots != 3"));
+        assert!(matches("name = backpack"));
+        assert!(matches("name == \"BACKPACK\""));
+        assert!(matches("craftable = true"));
+        assert!(matches("missingField = null"));
+        // A list matches if any element does
+        assert!(matches("foodValue = 0"));
+        assert!(!matches("foodValue > 0"));
+    }
+
+    #[test]
+    fn in_list() {
+        assert!(matches("clothing in [t, b, p]"));
+        assert!(matches("clothing in [P]"));
+        assert!(!matches("clothing in [t, b]"));
+        assert!(matches("numSlots in [1, 2, 4]"));
+    }
+
+    #[test]
+    fn in_range() {
+        assert!(matches("numSlots in 1..4"));
+        assert!(matches("numSlots in 4.."));
+        assert!(!matches("numSlots in 5.."));
+        assert!(matches("insulation in ..0.5"));
+        assert!(!matches("insulation in 0.1..0.5"));
+        assert!(!matches("missingField in 0.."));
+    }
+
+    #[test]
+    fn regex() {
+        assert!(matches("name ~ \"^Back\""));
+        assert!(!matches("name ~ \"^back\""));
+        assert!(matches("name ~ \"(?i)^back\""));
+        assert!(matches("id ~ \"^8$\""));
+        assert!("name ~ \"(\"".parse::<FilterExpression>().is_err());
+    }
+
+    #[test]
+    fn truthy_fields() {
+        assert!(matches("craftable"));
+        assert!(matches("numSlots and insulation"));
+        assert!(!matches("missingField"));
+        assert!(!matches("heldInHand and onelife.permanent"));
+    }
+
+    #[test]
+    fn source_prefixes() {
+        // Unprefixed fields come from twotech first, then OneLifeData7
+        assert!(matches("rValue = 0.2"));
+        assert!(matches("twotech.numSlots = 4"));
+        assert!(matches("onelife.numSlots = 4"));
+        assert!(matches("onelife.rValue = 0.2"));
+        assert!(!matches("twotech.rValue"));
+        assert!(!matches("onelife.insulation"));
+        assert!(matches("onelife.permanent.minPickupAge = 3"));
+        assert!(matches("twotech.id = 8 and onelife.id = 8"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        for expression in ["", "numSlots =", "(numSlots = 4", "numSlots = 4)", "numSlots in [1, 2", "name = \"open", "numSlots # 4"] {
+            assert!(expression.parse::<FilterExpression>().is_err(), "{expression} should not parse");
+        }
+    }
+}
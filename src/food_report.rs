@@ -0,0 +1,97 @@
+use serde::Serialize;
+
+use crate::game_database::GameDatabase;
+use crate::SharedGameObject;
+
+/// How much food an edible object gives, and how much work it takes to make
+#[derive(Clone, Debug, Serialize)]
+pub struct FoodEntry {
+    pub id: String,
+    pub name: String,
+    // Food from eating one serving (the first foodValue)
+    pub immediate: i32,
+    // Bonus food from one serving (the second foodValue)
+    pub bonus: i32,
+    // Servings before the object is used up (numUses)
+    pub servings: i32,
+    // (immediate + bonus) * servings
+    pub total: i32,
+    pub depth: Option<i32>,
+    // Transitions in its recipe, or its depth if twotech doesn't list the steps
+    pub steps: usize,
+    // Natural objects its recipe starts from, counting repeats (e.g. two Stones)
+    pub raw_ingredients: usize,
+    pub food_per_step: f64,
+    pub food_per_raw_ingredient: f64,
+}
+
+impl FoodEntry {
+    /// None if the object isn't edible
+    pub fn new(shared_obj: &SharedGameObject) -> Option<Self> {
+        let twotech = &shared_obj.twotech_data;
+        let food_value = twotech.foodValue.as_ref()?;
+        let immediate = food_value.first().copied().unwrap_or(0);
+        let bonus = food_value.get(1).copied().unwrap_or(0);
+        if immediate + bonus <= 0 {
+            return None;
+        }
+        let servings = twotech.numUses.unwrap_or(1).max(1);
+        let total = (immediate + bonus) * servings;
+        let recipe = twotech.recipe.as_ref();
+        let steps = recipe
+            .and_then(|recipe| recipe.steps.as_ref())
+            .map(|steps| steps.iter().map(|row| row.len()).sum())
+            .unwrap_or(twotech.depth.unwrap_or(0).max(0) as usize);
+        let raw_ingredients = recipe.and_then(|recipe| recipe.ingredients.as_ref()).map_or(0, |ingredients| ingredients.len());
+        Some(FoodEntry {
+            id: twotech.id.clone(),
+            name: twotech.name.clone(),
+            immediate,
+            bonus,
+            servings,
+            total,
+            depth: twotech.depth,
+            steps,
+            raw_ingredients,
+            // Natural food takes no steps or ingredients, so it counts as one of each
+            food_per_step: total as f64 / steps.max(1) as f64,
+            food_per_raw_ingredient: total as f64 / raw_ingredients.max(1) as f64,
+        })
+    }
+}
+
+/// Every edible object, with rankings by how efficiently each one turns work and resources into food
+#[derive(Clone, Debug, Serialize)]
+pub struct FoodReport {
+    // Sorted by total food, most first
+    pub foods: Vec<FoodEntry>,
+    // Object IDs, best first
+    pub by_food_per_step: Vec<String>,
+    pub by_food_per_raw_ingredient: Vec<String>,
+}
+
+impl FoodReport {
+    pub fn new(game_database: &GameDatabase) -> Self {
+        let mut foods = game_database
+            .objects
+            .values()
+            .filter(|shared_obj| !shared_obj.twotech_data.name.contains("removed"))
+            .filter_map(FoodEntry::new)
+            .collect::<Vec<_>>();
+        foods.sort_by(|a, b| b.total.cmp(&a.total).then(a.name.cmp(&b.name)));
+        let ranking = |efficiency: fn(&FoodEntry) -> f64| {
+            let mut ranked = foods.iter().collect::<Vec<_>>();
+            ranked.sort_by(|a, b| efficiency(b).total_cmp(&efficiency(a)).then(b.total.cmp(&a.total)));
+            ranked.into_iter().map(|food| food.id.clone()).collect::<Vec<_>>()
+        };
+        FoodReport {
+            by_food_per_step: ranking(|food| food.food_per_step),
+            by_food_per_raw_ingredient: ranking(|food| food.food_per_raw_ingredient),
+            foods,
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&FoodEntry> {
+        self.foods.iter().find(|food| food.id == id)
+    }
+}
@@ -0,0 +1,599 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::str::FromStr;
+use std::time::Instant;
+
+use anyhow::{anyhow, Context, Result};
+use glob::glob;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::intermediate_cache::{read_binary_cache, read_cache, write_binary_cache, write_cache, CacheFormat, CacheHeader};
+use crate::object_filter::{IngredientSet, ObjectFilter};
+use crate::one_life_data_category::{expand_category, CategoryData, CategoryMember};
+use crate::one_life_data_object::{BiomeId, OneLifeDataObject, SoundGroup};
+use crate::one_life_data_transition::{Transition, TransitionGraph};
+use crate::parse_error::ParseFailure;
+use crate::recipe_index::{IngredientIndex, RecipeIndex, UsedIn};
+use crate::twotech_generator::TwoTechGenerator;
+use crate::twotech_object::TwoTechObject;
+use crate::SharedGameObject;
+
+pub const INTERMEDIATE_FILES_DIR: &str = "intermediate-files";
+pub const ONELIFEDATA7_OBJECT_DATA_FILE: &str = "intermediate-files/OneLifeData7_Objects.json";
+pub const ONELIFEDATA7_CATEGORY_DATA_FILE: &str = "intermediate-files/OneLifeData7_Categories.json";
+pub const ONELIFEDATA7_TRANSITION_DATA_FILE: &str = "intermediate-files/OneLifeData7_Transitions.json";
+pub const TWOTECH_OBJECT_DATA_FILE: &str = "intermediate-files/twotech_Objects.json";
+pub const MERGED_OBJECT_DATA_FILE: &str = "intermediate-files/merged_Objects.msgpack";
+
+/// Where to find the game data, and whether the intermediate-files caches may be reused.
+#[derive(Clone, Debug)]
+pub struct LoadOptions {
+    pub one_life_data_directory: String,
+    pub twotech_data_directory: String,
+    pub regenerate_data: bool,
+    // Compute recipes, depths and tech trees from OneLifeData7 instead of reading twotech's output
+    pub generate_twotech_data: bool,
+    pub cache_format: CacheFormat,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions {
+            one_life_data_directory: "../../TwoHoursOneLife/OneLifeData7".to_string(),
+            twotech_data_directory: "../TwoTech-ProcessOutput".to_string(),
+            regenerate_data: false,
+            generate_twotech_data: false,
+            cache_format: CacheFormat::Json,
+        }
+    }
+}
+
+/// OneLifeData7 and twotech object data, merged by object ID into `SharedGameObject`s.
+#[derive(Clone, Debug, Default)]
+pub struct GameDatabase {
+    // Every object from each source, including those the other source doesn't have (and so aren't in `objects`)
+    pub one_life_objects: BTreeMap<String, OneLifeDataObject>,
+    pub twotech_objects: BTreeMap<String, TwoTechObject>,
+    pub objects: BTreeMap<String, SharedGameObject>,
+    // Object name (both the twotech and the OneLifeData7 name) => IDs of every object with that name
+    pub name_index: BTreeMap<String, Vec<String>>,
+    // Which objects are made from which, built from the merged objects' recipes
+    pub recipe_index: RecipeIndex,
+    // Every object's recursive ingredients, for the ingredient filters
+    pub ingredient_index: IngredientIndex,
+    pub transitions: TransitionGraph,
+    // Keyed by the category's parentID
+    pub categories: BTreeMap<i32, CategoryData>,
+    // Object files that couldn't be parsed, as recorded when the objects were parsed (so also when they came from a cache)
+    pub parse_failures: Vec<ParseFailure>,
+}
+
+// What the OneLifeData7 object cache holds: the objects that parsed, and why the others didn't
+#[derive(Serialize, Deserialize)]
+struct OneLifeObjects {
+    objects: BTreeMap<String, OneLifeDataObject>,
+    parse_failures: Vec<ParseFailure>,
+}
+
+// What the binary cache holds: everything `from_parts` would otherwise rebuild on every run
+#[derive(Serialize, Deserialize)]
+struct MergedObjects {
+    objects: BTreeMap<String, SharedGameObject>,
+    name_index: BTreeMap<String, Vec<String>>,
+    ingredient_index: IngredientIndex,
+    parse_failures: Vec<ParseFailure>,
+    // The objects only one source has. The rest of each source's objects are already in `objects`.
+    one_life_only_objects: BTreeMap<String, OneLifeDataObject>,
+    twotech_only_objects: BTreeMap<String, TwoTechObject>,
+}
+
+impl MergedObjects {
+    fn new(game_database: &GameDatabase) -> Self {
+        MergedObjects {
+            objects: game_database.objects.clone(),
+            name_index: game_database.name_index.clone(),
+            ingredient_index: game_database.ingredient_index.clone(),
+            parse_failures: game_database.parse_failures.clone(),
+            one_life_only_objects: game_database
+                .one_life_objects
+                .iter()
+                .filter(|(id, _)| !game_database.objects.contains_key(*id))
+                .map(|(id, obj)| (id.clone(), obj.clone()))
+                .collect(),
+            twotech_only_objects: game_database
+                .twotech_objects
+                .iter()
+                .filter(|(id, _)| !game_database.objects.contains_key(*id))
+                .map(|(id, obj)| (id.clone(), obj.clone()))
+                .collect(),
+        }
+    }
+
+    fn into_game_database(self) -> GameDatabase {
+        let mut one_life_objects = self.one_life_only_objects;
+        let mut twotech_objects = self.twotech_only_objects;
+        for (id, shared_obj) in &self.objects {
+            one_life_objects.insert(id.clone(), shared_obj.one_life_game_data.clone());
+            twotech_objects.insert(id.clone(), shared_obj.twotech_data.clone());
+        }
+        GameDatabase {
+            one_life_objects,
+            twotech_objects,
+            recipe_index: RecipeIndex::new(&self.objects),
+            objects: self.objects,
+            name_index: self.name_index,
+            ingredient_index: self.ingredient_index,
+            parse_failures: self.parse_failures,
+            ..Default::default()
+        }
+    }
+}
+
+/// The object IDs that satisfy one requested ingredient. Usually a single ID, but a category
+/// (e.g. "@ Any Berry Bowl") is satisfied by any of its members.
+pub type IngredientAlternatives = Vec<String>;
+
+impl GameDatabase {
+    /// Load OneLifeData7 and twotech object data (from the intermediate-files caches if present), and merge them.
+    pub fn load(options: &LoadOptions) -> Result<Self> {
+        if fs::read_dir(INTERMEDIATE_FILES_DIR).is_err() {
+            fs::create_dir(INTERMEDIATE_FILES_DIR).context("Could not create intermediate files directory")?;
+        }
+        // Force regeneration of intermediate-files
+        if options.regenerate_data {
+            println!("Generated data refresh triggered.");
+            println!(" > Removing intermediate-files for OneLifeData7 and twotech object data.");
+            fs::remove_file(ONELIFEDATA7_OBJECT_DATA_FILE).ok();
+            fs::remove_file(ONELIFEDATA7_TRANSITION_DATA_FILE).ok();
+            fs::remove_file(ONELIFEDATA7_CATEGORY_DATA_FILE).ok();
+            fs::remove_file(TWOTECH_OBJECT_DATA_FILE).ok();
+            fs::remove_file(MERGED_OBJECT_DATA_FILE).ok();
+        }
+        let transitions = TransitionGraph::new(load_one_life_transitions(&options.one_life_data_directory)?);
+        let categories = load_one_life_categories(&options.one_life_data_directory)?;
+        let merged_cache_headers = merged_cache_headers(options);
+        let merged_objects = match options.cache_format {
+            CacheFormat::Binary => read_binary_cache::<MergedObjects>(MERGED_OBJECT_DATA_FILE, merged_cache_headers.as_deref().ok()),
+            CacheFormat::Json => None,
+        };
+        let mut game_database = match merged_objects {
+            Some(merged_objects) => {
+                report_parse_failures(&merged_objects.parse_failures);
+                merged_objects.into_game_database()
+            }
+            None => {
+                let (one_life_objects, parse_failures) = load_one_life_objects(&options.one_life_data_directory)?;
+                let twotech_objects = if options.generate_twotech_data {
+                    let twotech_objects = TwoTechGenerator::new(&one_life_objects, &transitions, &categories).generate();
+                    println!("Generated {} twotech objects from OneLifeData7 data", twotech_objects.len());
+                    twotech_objects
+                } else {
+                    load_twotech_objects(&options.twotech_data_directory)?
+                };
+                let mut game_database = GameDatabase::from_parts(one_life_objects, twotech_objects);
+                game_database.parse_failures = parse_failures;
+                if options.cache_format == CacheFormat::Binary {
+                    write_binary_cache(MERGED_OBJECT_DATA_FILE, merged_cache_headers?, &MergedObjects::new(&game_database))?;
+                }
+                game_database
+            }
+        };
+        game_database.transitions = transitions;
+        game_database.categories = categories;
+        Ok(game_database)
+    }
+
+    /// Merge already-loaded OneLifeData7 and twotech objects. Only objects present in both sets are kept in `objects`.
+    pub fn from_parts(one_life_objects: BTreeMap<String, OneLifeDataObject>, twotech_objects: BTreeMap<String, TwoTechObject>) -> Self {
+        let mut objects = BTreeMap::new();
+        let mut name_index = BTreeMap::<String, Vec<String>>::new();
+        for (key, onelifedata_obj) in &one_life_objects {
+            name_index.entry(onelifedata_obj.name.clone()).or_default().push(key.to_owned());
+            if let Some(twotech_obj) = twotech_objects.get(key) {
+                if twotech_obj.name != onelifedata_obj.name {
+                    name_index.entry(twotech_obj.name.clone()).or_default().push(key.to_owned());
+                }
+                objects.insert(key.to_owned(), SharedGameObject::new(onelifedata_obj.to_owned(), twotech_obj.to_owned()));
+            }
+        }
+        GameDatabase {
+            one_life_objects,
+            twotech_objects,
+            recipe_index: RecipeIndex::new(&objects),
+            ingredient_index: IngredientIndex::new(&objects),
+            objects,
+            name_index,
+            transitions: TransitionGraph::default(),
+            categories: BTreeMap::new(),
+            parse_failures: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&SharedGameObject> {
+        self.objects.get(id)
+    }
+
+    /// Find an object by its exact twotech or OneLifeData7 name. If several objects share the name, the lowest ID wins.
+    pub fn find_by_name(&self, name: &str) -> Option<&SharedGameObject> {
+        self.name_index
+            .get(name)?
+            .iter()
+            .find_map(|id| self.objects.get(id))
+    }
+
+    /// Every craftable object whose recipe tree contains the given object (by name or ID), with the shortest chain of
+    /// crafting steps from it. A category matches any of its members. None if the name or ID doesn't match an object.
+    pub fn used_in(&self, name_or_id: &str, max_depth: Option<usize>) -> Option<Vec<UsedIn>> {
+        let id = self.resolve_object_id(name_or_id)?;
+        // A non-category expands to just itself, and a category's parent object can be an ingredient too
+        let mut start_ids = self.expand_category(id).iter().map(|member| member.id.to_string()).collect::<Vec<_>>();
+        if !start_ids.contains(&id.to_string()) {
+            start_ids.push(id.to_string());
+        }
+        let used_in = self.recipe_index
+            .used_in(&start_ids, max_depth)
+            .into_iter()
+            .filter(|used_in| self.get(&used_in.product_id).is_some_and(|o| o.twotech_data.craftable.unwrap_or(false)))
+            .collect();
+        Some(used_in)
+    }
+
+    /// Every sound ID played by an object, with the objects (by ID) that play it and the group they play it for
+    pub fn sound_usage(&self) -> BTreeMap<i32, Vec<(String, SoundGroup)>> {
+        let mut usage = BTreeMap::<i32, Vec<(String, SoundGroup)>>::new();
+        for (id, shared_obj) in &self.objects {
+            let Some(sounds) = &shared_obj.one_life_game_data.sounds else {
+                continue;
+            };
+            for (group, sound) in sounds.sounds() {
+                usage.entry(sound.id).or_default().push((id.clone(), group));
+            }
+        }
+        usage
+    }
+
+    /// Every naturally spawning object in each biome, by object ID, most common first
+    pub fn natural_objects_by_biome(&self) -> BTreeMap<BiomeId, Vec<(String, f64)>> {
+        let mut by_biome = BTreeMap::<BiomeId, Vec<(String, f64)>>::new();
+        for (id, shared_obj) in &self.objects {
+            for (biome, spawn_chance) in shared_obj.natural_biomes() {
+                by_biome.entry(biome).or_default().push((id.clone(), spawn_chance));
+            }
+        }
+        for objects in by_biome.values_mut() {
+            objects.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        }
+        by_biome
+    }
+
+    /// Expand a category ID into its concrete member objects, with the probability of each being picked.
+    /// An ID that isn't a category expands to just itself.
+    pub fn expand_category(&self, id: i32) -> Vec<CategoryMember> {
+        expand_category(&self.categories, id)
+    }
+
+    /// Find an object ID from a name or ID. Category parent objects (which twotech doesn't know about) are matched too.
+    pub fn resolve_object_id(&self, name_or_id: &str) -> Option<i32> {
+        if let Ok(id) = name_or_id.parse::<i32>() {
+            if self.objects.contains_key(name_or_id) || self.one_life_objects.contains_key(name_or_id) || self.categories.contains_key(&id) {
+                return Some(id);
+            }
+        }
+        self.find_by_name(name_or_id)
+            .map(|o| o.one_life_game_data.id)
+            .or_else(|| self.name_index.get(name_or_id)?.first()?.parse().ok())
+    }
+
+    /// Convert each ingredient set's names/IDs into the object IDs that satisfy them. Names that match no object are dropped.
+    pub fn resolve_ingredient_sets(&self, ingredient_sets: &[IngredientSet]) -> Vec<Vec<IngredientAlternatives>> {
+        ingredient_sets.iter()
+            .map(|ingredient_set| {
+                ingredient_set.0
+                .iter()
+                .filter_map(|ingredient| self.resolve_object_id(ingredient))
+                .map(|id| {
+                    self.expand_category(id)
+                        .into_iter()
+                        .map(|member| member.id.to_string())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    }
+
+    /// Every object matching all of the filter's criteria, keyed by object ID
+    pub fn filter_objects(&self, filter: &ObjectFilter) -> BTreeMap<String, SharedGameObject> {
+        self.objects
+            .iter()
+            .filter(|(_, shared_obj)| filter.matches(shared_obj))
+            .map(|(id, shared_obj)| (id.clone(), shared_obj.clone()))
+            .collect()
+    }
+
+    /// Keep objects whose recursive recipe tree contains every ingredient of at least one of the ingredient sets
+    pub fn filter_with_ingredients(&self, objects: BTreeMap<String, SharedGameObject>, ingredient_sets_to_find: &[Vec<IngredientAlternatives>]) -> BTreeMap<String, SharedGameObject> {
+        objects.into_iter()
+            .filter(|(_, obj)| {
+                // Instead of just looking for the one target ID, we need to look for the all the values in each set.
+                // If any set has all its values matched, we have a match!
+                ingredient_sets_to_find
+                    .iter()
+                    .any(|ingredient_set| self.contains_ingredient_set(obj, ingredient_set))
+            })
+            .collect()
+    }
+
+    /// Keep objects whose recursive recipe tree does not contain every ingredient of any of the ingredient sets
+    pub fn filter_without_ingredients(&self, objects: BTreeMap<String, SharedGameObject>, ingredient_sets_to_exclude: &[Vec<IngredientAlternatives>]) -> BTreeMap<String, SharedGameObject> {
+        objects.into_iter()
+            .filter(|(_, obj)| {
+                // We only want to keep objects that don't contain any of the ingredient sets in the query
+                !ingredient_sets_to_exclude
+                    .iter()
+                    .any(|ingredient_set| self.contains_ingredient_set(obj, ingredient_set))
+            })
+            .collect()
+    }
+
+    /// All ingredients (any one of each ingredient's alternatives) must be present in the object's recipe tree for the ingredient set to be a match
+    fn contains_ingredient_set(&self, obj: &SharedGameObject, ingredient_set: &[IngredientAlternatives]) -> bool {
+        ingredient_set
+            .iter()
+            .all(|alternatives| alternatives.iter().any(|i| self.ingredient_index.contains(&obj.twotech_data.id, i)))
+    }
+
+    /// Search the recipe tree of root_obj for target_id, returning the object whose recipe uses it
+    pub fn find_target_ingredient<'a>(&'a self, root_obj: &'a SharedGameObject, target_id: &str) -> Option<&'a SharedGameObject> {
+        let object_database = &self.objects;
+        let mut stack = Vec::new();
+        let mut visited = HashSet::new();
+        stack.push(root_obj);
+        while let Some(obj) = stack.pop() {
+            // If object has no ID or has already been visited, skip it
+            let obj_id = &obj.twotech_data.id;
+            if visited.contains(obj_id) {
+                continue;
+            }
+            // If current object is the ID we're looking for, return true!
+            if obj_id == target_id {
+                return Some(obj);
+            }
+            visited.insert(obj_id);
+
+            let obj_recipe = match &obj.twotech_data.recipe {
+                Some(recipe) => recipe,
+                None => continue,
+            };
+
+            // Check each ingredient for being the target_id, and if we haven't yet visited the ingredient, push it to the list
+            if let Some(ingredients) = obj_recipe.ingredients.as_ref().map(|ivec| HashSet::<&String>::from_iter(ivec.iter())) {
+                for ingredient in ingredients {
+                    if ingredient == target_id {
+                        return Some(obj);
+                    }
+                    if !visited.contains(ingredient) {
+                        if let Some(ingredient_object) = object_database.get(ingredient) {
+                            stack.push(ingredient_object);
+                        }
+                    }
+                }
+            }
+
+            // Push onto our stack all the unique values in the object recipe that we haven't yet visited
+            obj_recipe.steps
+            .as_ref()
+            .unwrap_or(&Vec::default())
+            .iter()
+            .flatten()
+            .flat_map(|rs| [rs.actorID.clone().unwrap_or("-1".to_string()), rs.targetID.clone().unwrap_or("-1".to_string())])
+            .filter(|ingredient| !visited.contains(ingredient))
+            .filter_map(|ingredient| object_database.get(&ingredient))
+            .for_each(|recipe_ingredient_object| stack.push(recipe_ingredient_object));
+        }
+        None
+    }
+}
+
+// The merged object cache depends on the OneLifeData7 objects, and either the twotech objects or (when generating them) the transitions
+fn merged_cache_headers(options: &LoadOptions) -> Result<Vec<CacheHeader>> {
+    let twotech_source = if options.generate_twotech_data {
+        CacheHeader::for_source(&options.one_life_data_directory, "transitions")?
+    } else {
+        CacheHeader::for_source(&options.twotech_data_directory, "public/static/objects")?
+    };
+    Ok(vec![CacheHeader::for_source(&options.one_life_data_directory, "objects")?, twotech_source])
+}
+
+// Try to load intermediate-files data into OneLifeData7 object data BTreeMap
+// If it didn't exist, recreate data and save to intermediate-files/OneLifeData7_Objects.json
+// If it parsed, great!
+// Files that fail to parse are skipped, and returned alongside the objects so every failure can be reported at once
+fn load_one_life_objects(one_life_data_directory: &str) -> Result<(BTreeMap<String, OneLifeDataObject>, Vec<ParseFailure>)> {
+    let cache_header = CacheHeader::for_source(one_life_data_directory, "objects");
+    if let Some(one_life_objects) = read_cache::<OneLifeObjects>(ONELIFEDATA7_OBJECT_DATA_FILE, cache_header.as_ref().ok()) {
+        report_parse_failures(&one_life_objects.parse_failures);
+        return Ok((one_life_objects.objects, one_life_objects.parse_failures));
+    }
+    println!("Intermediate file for OneLifeData7 object data is not present or out of date, we must regenerate it from OneLifeData7 data.");
+    if let Err(onelife_dir_err) = fs::read_dir(one_life_data_directory) {
+        println!("OneLifeData7 directory ({}) could not be opened, please provide different path via the -d option.", one_life_data_directory);
+        return Err(anyhow!(onelife_dir_err));
+    }
+    let cache_header = cache_header?;
+    let mut one_life_data_directory = one_life_data_directory.to_string();
+    if !one_life_data_directory.ends_with('/') {
+        one_life_data_directory.push('/');
+    }
+    let one_life_object_directory = one_life_data_directory + "objects/";
+    let one_life_object_dir_contents = fs::read_dir(one_life_object_directory).context("Could not read OneLifeData7 objects directory")?;
+    let object_file_regex = regex::Regex::new(r"^(\d+)\.txt$").unwrap();
+    let object_files = one_life_object_dir_contents
+        .flatten()
+        // Check if the entry is a file and matches the pattern
+        .filter(|entry| entry.metadata().is_ok_and(|metadata| metadata.is_file()))
+        .filter_map(|entry| {
+            let file_name = entry.file_name();
+            let object_id = object_file_regex.captures(&file_name.to_string_lossy())?.get(1)?.as_str().to_string();
+            Some((object_id, entry.path()))
+        })
+        .collect::<Vec<_>>();
+    let now = Instant::now();
+    // Every file parses independently, so spread them across all cores
+    let parse_results = object_files
+        .into_par_iter()
+        .map(|(object_id, path)| {
+            let contents = fs::read_to_string(&path).context(format!("Could not read OneLifeData7 object file {}", path.to_string_lossy()))?;
+            let object = OneLifeDataObject::from_str(&contents);
+            Ok((object_id, path, object))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut one_life_game_objects = BTreeMap::new();
+    let mut parse_failures = Vec::new();
+    for (object_id, path, object) in parse_results {
+        match object {
+            Ok(object) => {
+                one_life_game_objects.insert(object_id, object);
+            }
+            Err(error) => parse_failures.push(ParseFailure { path, error }),
+        }
+    }
+    println!("Parsed {} OneLifeData7 objects in {} seconds", one_life_game_objects.len(), (now.elapsed().as_millis() as f32)/1000.0);
+    parse_failures.sort_by(|a: &ParseFailure, b| a.path.cmp(&b.path));
+    report_parse_failures(&parse_failures);
+    let one_life_objects = OneLifeObjects { objects: one_life_game_objects, parse_failures };
+    write_cache(ONELIFEDATA7_OBJECT_DATA_FILE, cache_header, &one_life_objects)?;
+    Ok((one_life_objects.objects, one_life_objects.parse_failures))
+}
+
+// Printed whether the failures were just found or read back from a cache, so broken files are never silently missing
+fn report_parse_failures(parse_failures: &[ParseFailure]) {
+    if parse_failures.is_empty() {
+        return;
+    }
+    println!("{} OneLifeData7 object files could not be parsed and were skipped:", parse_failures.len());
+    for parse_failure in parse_failures {
+        println!(" > {parse_failure}");
+    }
+}
+
+// Same caching approach as the object data, but for OneLifeData7's transitions directory
+fn load_one_life_transitions(one_life_data_directory: &str) -> Result<Vec<Transition>> {
+    let cache_header = CacheHeader::for_source(one_life_data_directory, "transitions");
+    if let Some(transitions) = read_cache(ONELIFEDATA7_TRANSITION_DATA_FILE, cache_header.as_ref().ok()) {
+        return Ok(transitions);
+    }
+    println!("Intermediate file for OneLifeData7 transition data is not present or out of date, we must regenerate it from OneLifeData7 data.");
+    let cache_header = cache_header?;
+    let mut one_life_data_directory = one_life_data_directory.to_string();
+    if !one_life_data_directory.ends_with('/') {
+        one_life_data_directory.push('/');
+    }
+    let one_life_transition_directory = one_life_data_directory + "transitions/";
+    let one_life_transition_dir_contents = fs::read_dir(&one_life_transition_directory).context(format!("Could not read OneLifeData7 transitions directory ({one_life_transition_directory})"))?;
+    let transition_file_regex = regex::Regex::new(r"^-?\d+_-?\d+(_L[AT]?)?\.txt$").unwrap();
+    let transition_files = one_life_transition_dir_contents
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().is_some_and(|file_name| transition_file_regex.is_match(&file_name.to_string_lossy())))
+        .collect::<Vec<_>>();
+    let now = Instant::now();
+    let mut transitions = transition_files
+        .into_par_iter()
+        .map(|path| {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let contents = fs::read_to_string(&path).context("Could not read OneLifeData7 transition file")?;
+            match Transition::from_file(&file_name, &contents) {
+                Ok(transition) => Ok(Some(transition)),
+                Err(e) => {
+                    println!("Error converting file contents to transition: {} ({e})", path.to_string_lossy());
+                    Ok(None)
+                }
+            }
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    // Directory order isn't stable, so sort to keep the cache file diffable
+    transitions.sort_by_key(|t| (t.actorID, t.targetID, t.lastUseActor, t.lastUseTarget));
+    println!("Parsed {} OneLifeData7 transitions in {} seconds", transitions.len(), (now.elapsed().as_millis() as f32)/1000.0);
+    write_cache(ONELIFEDATA7_TRANSITION_DATA_FILE, cache_header, &transitions)?;
+    Ok(transitions)
+}
+
+// Same caching approach as the object data, but for OneLifeData7's categories directory
+fn load_one_life_categories(one_life_data_directory: &str) -> Result<BTreeMap<i32, CategoryData>> {
+    let cache_header = CacheHeader::for_source(one_life_data_directory, "categories");
+    if let Some(categories) = read_cache(ONELIFEDATA7_CATEGORY_DATA_FILE, cache_header.as_ref().ok()) {
+        return Ok(categories);
+    }
+    println!("Intermediate file for OneLifeData7 category data is not present or out of date, we must regenerate it from OneLifeData7 data.");
+    let cache_header = cache_header?;
+    let mut one_life_data_directory = one_life_data_directory.to_string();
+    if !one_life_data_directory.ends_with('/') {
+        one_life_data_directory.push('/');
+    }
+    let one_life_category_directory = one_life_data_directory + "categories/";
+    let one_life_category_dir_contents = fs::read_dir(&one_life_category_directory).context(format!("Could not read OneLifeData7 categories directory ({one_life_category_directory})"))?;
+    let category_file_regex = regex::Regex::new(r"^\d+\.txt$").unwrap();
+    let mut categories = BTreeMap::new();
+    for category_entry in one_life_category_dir_contents.flatten() {
+        let file_name = category_entry.file_name();
+        if !category_file_regex.is_match(&file_name.to_string_lossy()) {
+            continue;
+        }
+        let contents = fs::read_to_string(category_entry.path()).context("Could not read OneLifeData7 category file")?;
+        match CategoryData::from_str(&contents) {
+            Ok(category) => {
+                categories.insert(category.parentID, category);
+            }
+            Err(e) => println!("Error converting file contents to category: {} ({e})", category_entry.path().to_string_lossy()),
+        }
+    }
+    println!("Parsed {} OneLifeData7 categories", categories.len());
+    write_cache(ONELIFEDATA7_CATEGORY_DATA_FILE, cache_header, &categories)?;
+    Ok(categories)
+}
+
+fn load_twotech_objects(twotech_data_directory: &str) -> Result<BTreeMap<String, TwoTechObject>> {
+    let cache_header = CacheHeader::for_source(twotech_data_directory, "public/static/objects");
+    if let Some(twotech_objects) = read_cache(TWOTECH_OBJECT_DATA_FILE, cache_header.as_ref().ok()) {
+        return Ok(twotech_objects);
+    }
+    println!("Intermediate file for twotech object data is not present or out of date, we must regenerate it from twotech data.");
+    if let Err(twotech_dir_err) = fs::read_dir(twotech_data_directory) {
+        println!("TwoTech directory ({}) could not be opened, please provide different path via the -t option.", twotech_data_directory);
+        return Err(anyhow!(twotech_dir_err));
+    }
+    let cache_header = cache_header?;
+    let mut twotech_data_directory = twotech_data_directory.to_string();
+    if !twotech_data_directory.ends_with('/') {
+        twotech_data_directory.push('/');
+    }
+    let twotech_object_directory = twotech_data_directory + "public/static/objects/";
+
+    let twotech_object_files = glob(&format!("{twotech_object_directory}*.json"))
+        .context("Failed to read glob pattern")?
+        .filter_map(|entry| match entry {
+            Ok(path) => Some(path),
+            Err(e) => {
+                println!("entry error: {:?}", e);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    let now = Instant::now();
+    let twotech_objects = twotech_object_files
+        .into_par_iter()
+        .map(|path| {
+            let contents = fs::read_to_string(&path).context(format!("Unable to read {}", path.to_string_lossy()))?;
+            let object_data: TwoTechObject = serde_json::from_str(&contents).context(format!("Unable to parse JSON in {}", path.to_string_lossy()))?;
+            Ok((object_data.id.clone(), object_data))
+        })
+        .collect::<Result<BTreeMap<_, _>>>()?;
+    println!("Parsed {} twotech objects in {} seconds", twotech_objects.len(), (now.elapsed().as_millis() as f32)/1000.0);
+    write_cache(TWOTECH_OBJECT_DATA_FILE, cache_header, &twotech_objects)?;
+    Ok(twotech_objects)
+}
@@ -0,0 +1,214 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+
+use anyhow::{anyhow, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Bump this whenever the layout of anything written to intermediate-files changes,
+/// so caches written by older versions of thol-helper get rebuilt instead of misread.
+pub const CACHE_SCHEMA_VERSION: u32 = 10;
+
+const DATA_VERSION_FILE: &str = "dataVersionNumber.txt";
+
+/// Describes the source data a cache file was built from.
+/// A cache is only reused if its header matches the header computed from the source data as it is now.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CacheHeader {
+    pub schema_version: u32,
+    // Contents of dataVersionNumber.txt, if the source directory has one (twotech's output doesn't)
+    pub data_version: Option<String>,
+    // Canonicalized, so "../OneLifeData7" and "../OneLifeData7/" are the same source
+    pub source_directory: String,
+    pub fingerprint: SourceFingerprint,
+}
+
+/// A cheap stand-in for hashing every source file: changes whenever a file is added, removed or modified
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SourceFingerprint {
+    pub file_count: usize,
+    pub total_size: u64,
+    // Milliseconds since the Unix epoch
+    pub newest_modified: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile<T> {
+    header: CacheHeader,
+    data: T,
+}
+
+// Binary caches can be built from several sources (e.g. OneLifeData7 objects and twotech objects), so they carry a header for each
+#[derive(Serialize, Deserialize)]
+struct BinaryCacheFile<T> {
+    headers: Vec<CacheHeader>,
+    data: T,
+}
+
+/// How the merged object data is cached between runs.
+/// The per-source caches are always JSON, since they're also handy for poking at the parsed data by hand.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CacheFormat {
+    // Only the per-source JSON caches, merged again on every run
+    #[default]
+    Json,
+    // Also cache the merged objects as MessagePack, which skips both the JSON parsing and the merge
+    Binary,
+}
+
+impl FromStr for CacheFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(CacheFormat::Json),
+            "binary" | "bin" | "msgpack" => Ok(CacheFormat::Binary),
+            _ => Err(anyhow!("Unknown cache format {s} (expected json or binary)"))
+        }
+    }
+}
+
+impl CacheHeader {
+    /// Build the header for data parsed from `data_subdirectory` (e.g. "objects") of `source_directory`
+    pub fn for_source(source_directory: &str, data_subdirectory: &str) -> Result<Self> {
+        let source_path = Path::new(source_directory);
+        let canonical_source = fs::canonicalize(source_path).context(format!("Could not resolve source directory ({source_directory})"))?;
+        let data_version = fs::read_to_string(source_path.join(DATA_VERSION_FILE))
+            .ok()
+            .map(|version| version.trim().to_string());
+        Ok(CacheHeader {
+            schema_version: CACHE_SCHEMA_VERSION,
+            data_version,
+            source_directory: canonical_source.to_string_lossy().to_string(),
+            fingerprint: SourceFingerprint::for_directory(&source_path.join(data_subdirectory))?,
+        })
+    }
+
+    /// Why a cache with this header can't be used in place of data described by `current`, or None if it can
+    fn staleness_reason(&self, current: &CacheHeader) -> Option<String> {
+        if self.schema_version != current.schema_version {
+            Some(format!("cache schema version changed ({} -> {})", self.schema_version, current.schema_version))
+        } else if self.data_version != current.data_version {
+            Some(format!(
+                "data version changed ({} -> {})",
+                self.data_version.as_deref().unwrap_or("none"),
+                current.data_version.as_deref().unwrap_or("none"),
+            ))
+        } else if self.source_directory != current.source_directory {
+            Some(format!("source directory changed ({} -> {})", self.source_directory, current.source_directory))
+        } else if self.fingerprint != current.fingerprint {
+            Some("source files were added, removed or modified".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+impl SourceFingerprint {
+    pub fn for_directory(directory: &Path) -> Result<Self> {
+        let mut fingerprint = SourceFingerprint {
+            file_count: 0,
+            total_size: 0,
+            newest_modified: 0,
+        };
+        let directory_contents = fs::read_dir(directory).context(format!("Could not read {}", directory.to_string_lossy()))?;
+        for metadata in directory_contents.flatten().filter_map(|entry| entry.metadata().ok()) {
+            if !metadata.is_file() {
+                continue;
+            }
+            fingerprint.file_count += 1;
+            fingerprint.total_size += metadata.len();
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|since_epoch| since_epoch.as_millis() as u64)
+                .unwrap_or_default();
+            fingerprint.newest_modified = fingerprint.newest_modified.max(modified);
+        }
+        Ok(fingerprint)
+    }
+}
+
+/// Why a cache built from `cached` sources can't be used in place of data built from `current` sources, or None if it can
+fn sources_staleness_reason(cached: &[CacheHeader], current: &[CacheHeader]) -> Option<String> {
+    if cached.len() != current.len() {
+        return Some("the set of source directories changed".to_string());
+    }
+    cached
+        .iter()
+        .zip(current)
+        .find_map(|(cached, current)| cached.staleness_reason(current))
+}
+
+/// Read a cache file, returning None if it is missing, unreadable, or was built from different source data.
+/// If `current` is None (the source data couldn't be inspected), any cache written with the current schema is accepted.
+pub fn read_cache<T: DeserializeOwned>(cache_file: &str, current: Option<&CacheHeader>) -> Option<T> {
+    let cache_file_data = fs::read_to_string(cache_file).ok()?;
+    let cache = match serde_json::from_str::<CacheFile<T>>(&cache_file_data) {
+        Ok(cache) => cache,
+        Err(_) => {
+            println!("Intermediate file {cache_file} is unreadable or from an older thol-helper, it will be regenerated.");
+            return None;
+        }
+    };
+    let staleness_reason = match current {
+        Some(current) => cache.header.staleness_reason(current),
+        None if cache.header.schema_version != CACHE_SCHEMA_VERSION => Some("cache schema version changed".to_string()),
+        None => {
+            println!("Could not inspect the source data for {cache_file}, using it without checking whether it is up to date.");
+            None
+        }
+    };
+    if let Some(reason) = staleness_reason {
+        println!("Intermediate file {cache_file} is out of date ({reason}), it will be regenerated.");
+        return None;
+    }
+    Some(cache.data)
+}
+
+/// Write data to a cache file, along with the header describing the source data it was built from
+pub fn write_cache<T: Serialize>(cache_file: &str, header: CacheHeader, data: &T) -> Result<()> {
+    let cache = CacheFile { header, data };
+    fs::write(
+        cache_file,
+        serde_json::to_string_pretty(&cache).context(format!("Could not serialize data for caching in {cache_file}"))?
+    ).context(format!("Could not write to cache file {cache_file}"))
+}
+
+/// Like `read_cache`, but for a MessagePack cache file built from one or more sources
+pub fn read_binary_cache<T: DeserializeOwned>(cache_file: &str, current: Option<&[CacheHeader]>) -> Option<T> {
+    let cache_file_data = fs::read(cache_file).ok()?;
+    let cache = match rmp_serde::from_slice::<BinaryCacheFile<T>>(&cache_file_data) {
+        Ok(cache) => cache,
+        Err(_) => {
+            println!("Intermediate file {cache_file} is unreadable or from an older thol-helper, it will be regenerated.");
+            return None;
+        }
+    };
+    let staleness_reason = match current {
+        Some(current) => sources_staleness_reason(&cache.headers, current),
+        None if cache.headers.iter().any(|header| header.schema_version != CACHE_SCHEMA_VERSION) => Some("cache schema version changed".to_string()),
+        None => {
+            println!("Could not inspect the source data for {cache_file}, using it without checking whether it is up to date.");
+            None
+        }
+    };
+    if let Some(reason) = staleness_reason {
+        println!("Intermediate file {cache_file} is out of date ({reason}), it will be regenerated.");
+        return None;
+    }
+    Some(cache.data)
+}
+
+/// Write data to a MessagePack cache file, along with the headers describing the source data it was built from
+pub fn write_binary_cache<T: Serialize>(cache_file: &str, headers: Vec<CacheHeader>, data: &T) -> Result<()> {
+    let cache = BinaryCacheFile { headers, data };
+    // Fields are written by name rather than by position, so the data is as self-describing as the JSON caches
+    fs::write(
+        cache_file,
+        rmp_serde::to_vec_named(&cache).context(format!("Could not serialize data for caching in {cache_file}"))?
+    ).context(format!("Could not write to cache file {cache_file}"))
+}
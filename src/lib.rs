@@ -0,0 +1,125 @@
+pub mod bill_of_materials;
+pub mod craft_planner;
+pub mod creature_report;
+pub mod filter_expression;
+pub mod food_report;
+pub mod game_database;
+pub mod intermediate_cache;
+pub mod object_filter;
+pub mod one_life_data_category;
+pub mod one_life_data_object;
+pub mod one_life_data_transition;
+pub mod outfit;
+pub mod output_format;
+pub mod parse_error;
+pub mod query_results;
+pub mod recipe_index;
+pub mod roundtrip;
+#[cfg(test)]
+mod test_fixtures;
+pub mod twotech_generator;
+pub mod twotech_object;
+pub mod wiki_formats;
+
+pub use game_database::{GameDatabase, LoadOptions};
+pub use object_filter::{F32Range, I32Range, IngredientSet, ObjectFilter};
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use one_life_data_object::{BiomeId, OneLifeDataObject};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use twotech_object::TwoTechObject;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GameObject {
+    pub one_life_game_data: Option<OneLifeDataObject>,
+    pub twotech_data: Option<TwoTechObject>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SharedGameObject {
+    pub one_life_game_data: OneLifeDataObject,
+    pub twotech_data: TwoTechObject,
+    #[serde(skip)]
+    json: JsonCache,
+}
+
+// The object as a `serde_json::Value`, made the first time it's needed. Filter expressions, sorting, grouping and
+// output all look fields up in it, so it's worth only serializing each object once.
+#[derive(Clone, Default)]
+struct JsonCache(OnceLock<Value>);
+
+// Whether the JSON has been made yet doesn't change what the object is
+impl PartialEq for JsonCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for JsonCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("JsonCache")
+    }
+}
+
+// The field as_json adds the computed insulation under, in the OneLifeData7 section it's computed from
+const COMPUTED_INSULATION_FIELD: &str = "computedInsulation";
+
+// rValues are stored as f32, and twotech rounds, so insulation this close counts as the same
+const INSULATION_TOLERANCE: f64 = 0.0001;
+
+impl SharedGameObject {
+    pub fn new(one_life_game_data: OneLifeDataObject, twotech_data: TwoTechObject) -> Self {
+        SharedGameObject { one_life_game_data, twotech_data, json: JsonCache::default() }
+    }
+
+    /// The object as it's written out as JSON, which is what `--where` expressions, `--fields` and the like look fields up in.
+    /// The OneLifeData7 section also gets `computedInsulation` (see `insulation`), so it can be filtered and sorted on too.
+    /// Serialized on first use and kept, so don't change the object's data after calling this.
+    pub fn as_json(&self) -> &Value {
+        self.json.0.get_or_init(|| {
+            // Nothing in the object can fail to serialize (non-finite floats become null)
+            let mut json = serde_json::to_value(self).unwrap_or_default();
+            if let (Some(one_life), Some(insulation)) = (json["one_life_game_data"].as_object_mut(), self.insulation()) {
+                one_life.insert(COMPUTED_INSULATION_FIELD.to_string(), insulation.into());
+            }
+            json
+        })
+    }
+
+    /// Insulation computed from the OneLifeData7 rValue and clothing slot, rather than taken from twotech
+    pub fn insulation(&self) -> Option<f64> {
+        self.one_life_game_data.insulation()
+    }
+
+    /// The computed and twotech insulation, if they disagree. Missing values count as 0.
+    pub fn insulation_mismatch(&self) -> Option<(f64, f64)> {
+        let computed = self.insulation().unwrap_or(0.0);
+        let twotech = self.twotech_data.insulation.unwrap_or(0.0);
+        Some((computed, twotech)).filter(|_| (computed - twotech).abs() > INSULATION_TOLERANCE)
+    }
+
+    /// Every biome the object spawns in naturally, with its chance of spawning there.
+    /// twotech's per-biome spawnChance is used where it has one, otherwise OneLifeData7's mapChance.
+    pub fn natural_biomes(&self) -> Vec<(BiomeId, f64)> {
+        let Some(map_chance) = self.one_life_game_data.mapChance.as_ref().filter(|m| m.mapChance > 0.0) else {
+            return Vec::new();
+        };
+        let twotech_biomes = self.twotech_data.biomes.as_deref().unwrap_or_default();
+        map_chance
+            .biomes
+            .iter()
+            .flatten()
+            .map(|&biome| {
+                let spawn_chance = twotech_biomes
+                    .iter()
+                    .find(|twotech_biome| twotech_biome.biome_id() == Some(biome))
+                    .and_then(|twotech_biome| twotech_biome.spawnChance)
+                    .unwrap_or(map_chance.mapChance as f64);
+                (biome, spawn_chance)
+            })
+            .collect()
+    }
+}
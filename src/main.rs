@@ -1,30 +1,24 @@
-mod one_life_data_object;
-mod twotech_object;
-mod wiki_formats;
-
 use std::collections::BTreeMap;
-use std::collections::HashSet;
-use std::io::Read;
 use std::io::Write;
-use std::fs::{self, File};
-use std::io::BufReader;
-use std::ops::RangeInclusive;
-use std::str::FromStr;
 use std::time::Instant;
 
-use anyhow::Context;
-use anyhow::{anyhow, Result};
-use clap::Parser;
-use glob::glob;
-use one_life_data_object::OneLifeDataObject;
-use one_life_data_object::SlotStyle;
-use serde::Deserialize;
-use serde::Serialize;
-use strum::IntoEnumIterator;
-use twotech_object::MoveType;
-use twotech_object::{ClothingType, TwoTechObject};
-use serde_json::Value;
-use wiki_formats::_generate_wiki_cards;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use thol_helper::bill_of_materials::BillOfMaterials;
+use thol_helper::craft_planner::{PathCost, ReachableObjects, RecipeWait, TransitionPath};
+use thol_helper::creature_report::creatures;
+use thol_helper::filter_expression::FilterExpression;
+use thol_helper::food_report::{FoodEntry, FoodReport};
+use thol_helper::intermediate_cache::CacheFormat;
+use thol_helper::object_filter::{parse_clothing_types, parse_move_types};
+use thol_helper::one_life_data_object::{BiomeId, SlotStyle, SoundGroup};
+use thol_helper::outfit::{Outfit, OutfitGoal};
+use thol_helper::output_format::{format_objects, OutputFormat};
+use thol_helper::query_results::{QueryResults, SortKey};
+use thol_helper::roundtrip::verify_object_roundtrip;
+use thol_helper::twotech_object::DecayTime;
+use thol_helper::wiki_formats::{WikiTemplate, _generate_wiki_cards};
+use thol_helper::{F32Range, GameDatabase, I32Range, IngredientSet, LoadOptions, ObjectFilter, SharedGameObject};
 
 const DEFAULT_OUTOUT_FILENAME: &str = "output.json";
 
@@ -35,6 +29,9 @@ const DEFAULT_OUTOUT_FILENAME: &str = "output.json";
     about = r#"Filter twotech's object data for objects that interest you."#
 )]
 pub struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(
         short = 'o',
         long,
@@ -47,7 +44,7 @@ pub struct Args {
         short = 'r',
         long,
         default_value = "false",
-        help = "Refresh cached OneLifeData7 and twotech data",
+        help = "Refresh cached OneLifeData7 and twotech data, even if it looks up to date",
     )]
     regenerate_data: bool,
 
@@ -55,12 +52,47 @@ pub struct Args {
     one_life_data_directory: String,
     #[arg(short = 't', long, default_value = "../TwoTech-ProcessOutput")]
     twotech_data_directory: String,
-    // Don't worry about this...it's an unfinished option that will possibly be broken out into another program.
-    // The idea is to convert an object list into table entries for a wiki page. It's too hard-coded though, and again, should also be broken out.
-    #[arg(long, default_value = "false")]
-    wiki_table_output: bool,
+    #[arg(
+        short = 'g',
+        long,
+        default_value = "false",
+        help = "Generate recipe and tech tree data from OneLifeData7 instead of reading twotech's output (-t is ignored)",
+    )]
+    generate_twotech_data: bool,
+    #[arg(
+        long,
+        default_value = "json",
+        help = "json => only cache each data source as JSON, binary => also cache the merged objects in a compact binary file that loads much faster",
+    )]
+    cache_format: CacheFormat,
+    #[arg(
+        long,
+        help = "Write the objects as a wiki table, using a built-in template (slots, food, clothing, movers, single-mover or cards) or a template file",
+    )]
+    wiki_table: Option<String>,
     #[arg(long, default_value = "false")]
     generate_wiki_cards: bool,
+    #[arg(long, default_value = "json", help = "json, pretty-json, ndjson, csv, tsv or markdown")]
+    format: OutputFormat,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Fields to write (comma-separated), looked up like in --where, e.g. name,numSlots,slotSize,insulation,onelife.rValue. JSON formats write whole objects without this",
+    )]
+    fields: Vec<String>,
+    #[arg(long, default_value = "false", help = "Write JSON as a list of objects rather than an object keyed by object name")]
+    json_list: bool,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        allow_hyphen_values = true,
+        help = "Fields to sort by (comma-separated), each optionally followed by :asc or :desc (or starting with - for descending), e.g. insulation:desc,name",
+    )]
+    sort_by: Vec<SortKey>,
+    #[arg(long, help = "Only write this many objects (per group, with --group-by)")]
+    limit: Option<usize>,
+    #[arg(long, help = "Group the objects by a field, e.g. clothing or moveType")]
+    group_by: Option<String>,
 
 // Filtering options
     #[arg(long)]
@@ -82,14 +114,14 @@ pub struct Args {
     total_food_value: Option<I32Range>,
     #[arg(
         long,
-        help = "Filter for specific ingredient(s) being present in object's recursive recipe trees (comma-separated, can use object name or ID).
+        help = "Filter for specific ingredient(s) being present in object's recursive recipe trees (comma-separated, can use object name or ID, and categories match any of their members).
 Specify multiple times for logical OR across specified lists",
         value_parser = clap::value_parser!(IngredientSet),
     )]
     with_ingredients: Option<Vec<IngredientSet>>,
     #[arg(
         long,
-        help = "Filter for specific ingredient(s) being present in object's recursive recipe trees (comma-separated, can use object name or ID).
+        help = "Filter for specific ingredient(s) being present in object's recursive recipe trees (comma-separated, can use object name or ID, and categories match any of their members).
     Specify multiple times for logical OR across specified lists",
         value_parser = clap::value_parser!(IngredientSet),
     )]
@@ -104,504 +136,470 @@ Specify multiple times for logical OR across specified lists",
         help = "Comma-separated for multiple. 0=>None, 1=>Chase, 2=>Flee, 3=>Random, 4-7=>N,S,E,W"
     )]
     move_type: Option<String>,
+    #[arg(long, value_delimiter = ',', help = "Objects that play any of these sound IDs (comma-separated)")]
+    uses_sound: Vec<i32>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Objects with no sound in any of these groups (comma-separated: creation, using, eating, decay)",
+    )]
+    no_sound: Vec<SoundGroup>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Objects that spawn naturally in any of these biomes (comma-separated IDs or names, e.g. 0,swamp,\"Yellow Prairies\")",
+    )]
+    biome: Vec<BiomeId>,
+    #[arg(long, help = "Objects that decay on their own within this long, e.g. 30s, 5m, 1h")]
+    decays_within: Option<DecayTime>,
+    #[arg(long, help = "Objects that last at least this long when left alone (including ones that never decay), e.g. 30s, 5m, 1h, epoch")]
+    min_decay: Option<DecayTime>,
+    #[arg(
+        long = "where",
+        help = "Filter expression over any twotech/OneLifeData7 field, e.g. 'numSlots >= 2 and (clothing in [t,b] or insulation > 0.1) and not name ~ \"Removed\"'",
+    )]
+    where_expression: Option<FilterExpression>,
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
-pub struct GameObject {
-    pub one_life_game_data: Option<OneLifeDataObject>,
-    pub twotech_data: Option<TwoTechObject>,
-}
-
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
-pub struct SharedGameObject {
-    pub one_life_game_data: OneLifeDataObject,
-    pub twotech_data: TwoTechObject,
+#[derive(Subcommand)]
+enum Command {
+    /// Parse every OneLifeData7 object file, write it back out, and report a diff of any differences
+    VerifyRoundtrip,
+    /// List every craftable object whose recipe tree contains the given object, grouped by how many crafting steps away it is
+    UsedIn {
+        /// Object name or ID
+        object: String,
+        /// Only look this many crafting steps ahead
+        #[arg(long)]
+        max_depth: Option<usize>,
+    },
+    /// List every sound ID and the objects that play it, or just the objects for the given sound IDs
+    SoundUsage {
+        sound_ids: Vec<i32>,
+    },
+    /// Total up the raw materials, tools and intermediate objects needed to make an object
+    Bom {
+        /// Object name or ID
+        object: String,
+        /// How many of the object to make
+        #[arg(long, default_value = "1")]
+        quantity: u32,
+        /// Print the bill of materials as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List every object that can be made from the given objects without any other inputs
+    Reachable {
+        /// Object names or IDs on hand
+        #[arg(required = true)]
+        objects: Vec<String>,
+        /// Only follow this many rounds of transitions
+        #[arg(long)]
+        max_steps: Option<usize>,
+        /// Treat natural objects (mapChance > 0) as free inputs
+        #[arg(long)]
+        natural: bool,
+        /// Print the reachable objects as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Find the shortest sequence of transitions (including decays) turning one object into another
+    Path {
+        /// Object name or ID to start from
+        from: String,
+        /// Object name or ID to end up with
+        to: String,
+        /// steps => fewest transitions, wait => least time waiting on decays
+        #[arg(long, default_value = "steps")]
+        by: PathCost,
+        /// Print the path as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List every naturally spawning object in each biome, most common first, or just the given biomes
+    Biomes {
+        biomes: Vec<BiomeId>,
+    },
+    /// Pick the best item for each body slot (head, top, bottom, both shoes, pack) out of the objects matching the filters
+    Outfit {
+        /// insulation, slots, or weighted (insulation-weight * insulation + slots-weight * numSlots)
+        #[arg(long, default_value = "insulation")]
+        maximize: OutfitGoal,
+        #[arg(long, default_value = "1.0")]
+        insulation_weight: f64,
+        #[arg(long, default_value = "0.05")]
+        slots_weight: f64,
+        /// Only use clothing with a recipe at most this deep
+        #[arg(long)]
+        max_depth: Option<i32>,
+        /// Print the outfit as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List clothing whose insulation, computed from its rValue and clothing slot, disagrees with twotech's
+    CheckInsulation,
+    /// List every edible object's immediate, bonus and total food, and rank them by food per recipe step and per raw ingredient
+    FoodReport {
+        /// How many objects to list in each ranking
+        #[arg(long, default_value = "20")]
+        top: usize,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List every creature's movement, danger radius, biomes and the transitions that tame, kill or butcher it
+    Creatures {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List every decay waited on while following an object's recipe, and the total time spent waiting
+    RecipeWait {
+        /// Object name or ID
+        object: String,
+        /// Print the waits as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
-#[derive(Debug, Clone)]
-pub struct IngredientSet(Vec<String>);
-
-impl FromStr for IngredientSet {
-    type Err = std::num::ParseIntError;
+impl Args {
+    fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            one_life_data_directory: self.one_life_data_directory.clone(),
+            twotech_data_directory: self.twotech_data_directory.clone(),
+            regenerate_data: self.regenerate_data,
+            generate_twotech_data: self.generate_twotech_data,
+            cache_format: self.cache_format,
+        }
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(IngredientSet(s.split(',').map(|s| s.to_string()).collect::<Vec<String>>()))
+    fn object_filter(&self) -> Result<ObjectFilter> {
+        Ok(ObjectFilter {
+            clothing: self.clothing.as_deref().map(parse_clothing_types).transpose()?.unwrap_or_default(),
+            move_types: self.move_type.as_deref().map(parse_move_types).transpose()?.unwrap_or_default(),
+            min_pickup_age: self.min_pickup_age,
+            slot_size: self.slot_size.clone(),
+            num_slots: self.num_slots.clone(),
+            is_food: self.is_food,
+            immediate_food_value: self.immediate_food_value.clone(),
+            bonus_food_value: self.bonus_food_value.clone(),
+            total_food_value: self.total_food_value.clone(),
+            container_slot_type: self.container_slot_type.clone(),
+            uses_sound: self.uses_sound.clone(),
+            without_sound: self.no_sound.clone(),
+            biomes: self.biome.clone(),
+            decays_within: self.decays_within.clone(),
+            min_decay: self.min_decay.clone(),
+            expression: self.where_expression.clone(),
+        })
     }
 }
 
 fn main() -> Result<()> {
     let start = Instant::now();
-    let now = start.clone();
-    const INTERMEDIATE_FILES_DIR: &str = "intermediate-files";
-    const ONELIFEDATA7_OBJECT_DATA_FILE: &str = "intermediate-files/OneLifeData7_Objects.json";
-    const TWOTECH_OBJECT_DATA_FILE: &str = "intermediate-files/twotech_Objects.json";
+    let now = start;
 
     let args = Args::parse();
+    if let Some(command) = &args.command {
+        return run_command(command, &args);
+    }
     // If the user specified the wiki output option, but didn't specify an output file, the defaul output.json will be misleading.
     // Warn the user and ask them to say yes to continue.
     let mut wiki_output_file_check = true;
-    if args.wiki_table_output && args.output_file.as_str() == DEFAULT_OUTOUT_FILENAME {
+    if args.wiki_table.is_some() && args.output_file.as_str() == DEFAULT_OUTOUT_FILENAME {
         let err_msg = format!("Wiki output selected, but default {DEFAULT_OUTOUT_FILENAME} file still being used+. To avoid confusion, perhaps specify a different value with the -o option?");
         wiki_output_file_check = pause(Some(err_msg));
     }
     if !wiki_output_file_check {
         return Ok(());
     }
-    let mut clothing_to_match = Vec::new();
-    if args.clothing.is_some() {
-        clothing_to_match =
-            args.clothing
-            .unwrap_or_default()
-            .split(",")
-            .collect::<Vec<_>>()
-            .iter()
-            .map(|c| ClothingType::from_str(c).unwrap())
-            .collect::<Vec<_>>();
-    }
-    let mut move_types_to_match = Vec::new();
-    if args.move_type.is_some() {
-        let  move_type_args = args.move_type.unwrap_or_default();
-        let move_type_arg_parts = move_type_args.split(",").collect::<Vec<_>>();
-        if move_type_arg_parts.iter().any(|p| p.to_lowercase().as_str() == "all") {
-            move_types_to_match = MoveType::iter().collect();
-        } else if move_type_arg_parts.iter().any(|p| {
-            p.to_lowercase().replace("_", "").replace(" ", "").as_str() == "notnone"
-        }) {
-            move_types_to_match = MoveType::iter().filter(|move_type| move_type != &MoveType::None).collect();
-        } else {
-            move_types_to_match = move_type_arg_parts
-                .iter()
-                .map(|move_type| {
-                    MoveType::from_str(move_type).expect(&format!("Could not parse argument string into MoveType: {move_type}"))
-                })
-                .collect::<Vec<_>>();
-        }
-    }
-    // numSlots filter. Default is all values > 0
-    let num_slots_filter = args.num_slots
-        .clone()
-        .unwrap_or(I32Range(RangeInclusive::new(0, i32::MAX)))
-        .0;
-
-    // slotSize filter. Default is all values
-    let slot_size_filter = args.slot_size
-        .clone()
-        .unwrap_or(F32Range(RangeInclusive::new(f32::MIN, f32::MAX)))
-        .0;
-
-    if fs::read_dir(INTERMEDIATE_FILES_DIR).is_err() {
-        fs::create_dir(INTERMEDIATE_FILES_DIR).context("Could not create intermediate files directory")?;
-    }
-    // Force regeneration of intermediate-files
-    if args.regenerate_data {
-        println!("Generated data refresh triggered.");
-        println!(" > Removing intermediate-files for OneLifeData7 and twotech object data.");
-        fs::remove_file(ONELIFEDATA7_OBJECT_DATA_FILE).ok();
-        fs::remove_file(TWOTECH_OBJECT_DATA_FILE).ok();
-    }
-    // Try to load intermediate-files data into OneLifeData7 object data BTreeMap
-    // If the file exists but doesn't make sense, delete it
-    // If it didn't make sense or it didn't exist, recreate data and save to intermediate-files/OneLifeData7_Objects.json
-    // If it parsed, great!
-    let initial_one_life_game_objects = if let Ok(one_life_file_data) = fs::read_to_string(ONELIFEDATA7_OBJECT_DATA_FILE) {
-        serde_json::from_str::<BTreeMap<String, OneLifeDataObject>>(one_life_file_data.as_str()).context("Could not parse OneLifeData7 JSON data into Rust objects")?
-    } else {
-        println!("Intermediate file for OneLifeData7 object data is not present, we must regenerate it from OneLifeData7 data.");
-        if let Err(onelife_dir_err) = fs::read_dir(&args.one_life_data_directory) {
-            println!("OneLifeData7 directory ({}) could not be opened, please provide different path via the -d option.", args.one_life_data_directory);
-            return Err(anyhow!(onelife_dir_err));
-        }
-        let mut one_life_data_directory = args.one_life_data_directory;
-        if !one_life_data_directory.ends_with("/") {
-            one_life_data_directory.push('/');
-        }
-        let one_life_object_directory = one_life_data_directory + "objects/";
-        let one_life_object_dir_contents = fs::read_dir(one_life_object_directory).context("Could not read OneLifeData7 objects directory")?;
-        let mut one_life_game_objects = BTreeMap::new();
-        for one_life_data_entry in one_life_object_dir_contents {
-            if let Ok(one_life_data_entry) = one_life_data_entry {
-                // Check if the entry is a file and matches the pattern
-                if let Ok(metadata) = one_life_data_entry.metadata() {
-                    if metadata.is_file() {
-                        let file_name = one_life_data_entry.file_name();
-                        let file_name = file_name.to_string_lossy();
-                        if let Some(captures) = regex::Regex::new(r"^(\d+)\.txt$").unwrap().captures(&file_name) {
-                            // For debugging, only look at file we care about
-                            // if captures.get(1).unwrap().as_str() != "14492" {
-                            //     continue;
-                            // }
-                            // println!("Parsing file {file_name}");
-                            // Read the file into a string
-                            let object_id = match captures.get(1) {
-                                Some(id) => id.as_str().to_string(),
-                                None => continue,
-                            };
-                            let mut file = fs::File::open(one_life_data_entry.path()).unwrap();
-                            let mut contents = String::new();
-                            file.read_to_string(&mut contents).unwrap();
-                            if let Ok(object) = OneLifeDataObject::from_str(&contents) {
-                                one_life_game_objects.insert(object_id, object);
-                            } else {
-                                println!("Error converting file contents to object: {}", one_life_data_entry.path().to_string_lossy());
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        println!("Parsed {} OneLifeData7 objects", one_life_game_objects.len());
-        fs::write(
-            ONELIFEDATA7_OBJECT_DATA_FILE, 
-            serde_json::to_string_pretty(&one_life_game_objects).context("Could not serialize OneLifeData7 data for caching")?
-        ).context("Could not write to OneLifeData7 object cache file")?;
-        one_life_game_objects
-    };
-
-    let initial_twotech_objects = if let Ok(twotech_file_data) = fs::read_to_string(TWOTECH_OBJECT_DATA_FILE) {
-        serde_json::from_str::<BTreeMap<String, TwoTechObject>>(twotech_file_data.as_str()).context("Could not parse twotech JSON data into Rust objects")?
-    } else {
-        println!("Intermediate file for twotech object data is not present, we must regenerate it from twotech data.");
-        if let Err(twotech_dir_err) = fs::read_dir(&args.twotech_data_directory) {
-            println!("TwoTech directory ({}) could not be opened, please provide different path via the -t option.", args.twotech_data_directory);
-            return Err(anyhow!(twotech_dir_err));
-        }
-        let mut twotech_data_directory = args.twotech_data_directory;
-        if !twotech_data_directory.ends_with("/") {
-            twotech_data_directory.push('/');
-        }
-        let twotech_object_directory = twotech_data_directory.clone() + "public/static/objects/";
-
-        let mut twotech_objects = BTreeMap::new();
-        for entry in glob(&format!("./{twotech_object_directory}/*.json")).context("Failed to read glob pattern")? {
-            match entry {
-                Ok(path) => {
-                    let file = File::open(&path).context("Unable to open file")?;
-                    let reader = BufReader::new(file);
-                    let json: Value = serde_json::from_reader(reader).context("Unable to parse JSON")?;
-                    let json_string = serde_json::to_string_pretty(&json).context("Could not serialize JSON value to String")?;
-                    let object_data: TwoTechObject = serde_json::from_str(&json_string).context(format!("JSON:\n{}", json_string))?;
-                    twotech_objects.insert(object_data.id.clone(), object_data);
-                }
-                Err(e) => println!("entry error: {:?}", e),
-            }
-        }
-        println!("Parsed {} twotech objects", twotech_objects.len());
-        fs::write(
-            TWOTECH_OBJECT_DATA_FILE,
-            serde_json::to_string_pretty(&twotech_objects).context("Could not serialize twotech data for caching")?
-        ).context("Could not write to twotech object cache file")?;
-        twotech_objects
-    };
-
-    let mut initial_shared_objects = BTreeMap::new();
-    for (key, onelifedata_obj) in &initial_one_life_game_objects {
-        if let Some(twotech_obj) = initial_twotech_objects.get(key) {
-            initial_shared_objects.insert(key.to_owned(), SharedGameObject {
-                one_life_game_data: onelifedata_obj.to_owned(),
-                twotech_data: twotech_obj.to_owned(),
-            });
-        } else {
-            // println!("OneLifeData7 contained ID {}, name {}, but twotech did not!", onelifedata_obj.id, onelifedata_obj.name);
-        }
-    }
-    for (key, twotech_obj) in &initial_twotech_objects {
-        if !initial_shared_objects.contains_key(key) {
-            if let Some(onelifedata_obj) = initial_one_life_game_objects.get(key) {
-                initial_shared_objects.insert(key.to_owned(), SharedGameObject {
-                    one_life_game_data: onelifedata_obj.to_owned(),
-                    twotech_data: twotech_obj.to_owned(),
-                });
-            } else {
-                // println!("OneLifeData7 contained ID {}, name {}, but twotech did not!", twotech_obj.id.as_ref().unwrap_or(&"ERR: NO ID".to_string()), twotech_obj.name.as_ref().unwrap_or(&"ERR: NO NAME".to_string()));
-            }
-        }
-    }
+    // Build the filter (and the template) before loading the game data, so a typo in --where fails fast
+    let object_filter = args.object_filter()?;
+    let wiki_template = args.wiki_table.as_deref().map(WikiTemplate::load).transpose()?;
 
-    let mut shared_objects = initial_shared_objects.clone();
+    let game_database = GameDatabase::load(&args.load_options())?;
     let elapsed = now.elapsed();
     println!("Initial shared object creation took {} seconds", (elapsed.as_millis() as f32)/1000.0);
     let now = Instant::now();
 
-    // Prepare ingredient sets to exclude based on user input
-    let ingredient_sets_to_exclude = args.without_ingredients
-        .map(|ingredient_sets| {
-            ingredient_sets.into_iter()
-            .map(|ingredient_set| {
-                // Convert ingredient set into an option that either has the converted ingredient name into an ID, the ID as-provided, or None
-                ingredient_set.0
-                .into_iter()
-                .filter_map(|ingredient| {
-                    initial_shared_objects
-                        .iter()
-                        .map(|(_, obj)| &obj.twotech_data)
-                        .find(|o| o.name == ingredient)
-                        .map(|o| o.id.clone())
-                })
-                .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>()
-        });
-
-    // Prepare ingredient sets to find based on user input
-    let ingredient_sets_to_find = args.with_ingredients
-        // Act on args.needs_ingredients if it is present
-        .map(|ingredient_sets| {
-            // Iterate through all the sets of ingredients the user provided (via separately defined option calls)
-            ingredient_sets.into_iter()
-            .map(|ingredient_set| {
-                // Convert ingredient set into an option that either has the converted ingredient name into an ID, the ID as-provided, or None
-                ingredient_set.0
-                .into_iter()
-                .filter_map(|ingredient| {
-                    initial_shared_objects
-                        .iter()
-                        .map(|(_, obj)| &obj.twotech_data)
-                        .find(|o| o.name == ingredient)
-                        .map(|o| o.id.clone())
-                })
-                .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>()
-        });
-
-    let elapsed = now.elapsed();
-    println!("Ingredient set parsing took {} seconds", (elapsed.as_millis() as f32)/1000.0);
-    let now = Instant::now();
-
-    shared_objects = shared_objects.into_iter()
-        .filter(|(_, shared_obj)| {
-            let onelifedata_obj = &shared_obj.one_life_game_data;
-            let twotech_obj = &shared_obj.twotech_data;
-            twotech_obj.craftable.unwrap_or(false)
-            // Specific type of clothing
-            && (
-                clothing_to_match.is_empty()
-                || twotech_obj.clothing.as_ref().is_some_and(|clothing| clothing_to_match.contains(clothing))
-            )
-            && (
-                move_types_to_match.is_empty()
-                || twotech_obj.moveType.as_ref().is_some_and(|move_type| move_types_to_match.contains(move_type))
-            )
-            // Is over minimum pickup age filter (0 if not specified)
-            && twotech_obj.minPickupAge.unwrap_or(0) >= args.min_pickup_age
-            // Number of slots for item falls within specified range (default is all positive values)
-            && num_slots_filter.contains(&twotech_obj.numSlots.unwrap_or(0))
-            // slotSize is for item falls within specified range (default is all values allowed)
-            && slot_size_filter.contains(&twotech_obj.slotSize.unwrap_or(f32::MIN))
-            // User either wants to filter for items being food or not food, or args.is_food will be None
-            && (
-                args.is_food.is_none() ||
-                twotech_obj.foodValue.as_ref().is_some_and(|f| f.len() > 0) == args.is_food.unwrap()
-            )
-            // Total food supplied by the item, including immediate food and bonus
-            && (
-                args.total_food_value.is_none()
-                || twotech_obj.foodValue.as_ref().is_some_and(|f| {
-                    let food_value_filter = args.total_food_value
-                        .clone()
-                        .unwrap() // Okay to do because we're in the else if an is_none()
-                        .0;
-                    food_value_filter.contains(&f.into_iter().sum())
-                })
-            )
-            // Immediate food supplied by the item
-            && (
-                args.immediate_food_value.is_none()
-                || twotech_obj.foodValue.as_ref().is_some_and(|f| {
-                    let food_value_filter = args.immediate_food_value
-                        .clone()
-                        .unwrap()
-                        .0;
-                    food_value_filter.contains(&f.into_iter().sum())
-                })
-            )
-            // Bonus food supplied by the item
-            && (
-                args.bonus_food_value.is_none()
-                || twotech_obj.foodValue.as_ref().is_some_and(|f| {
-                    let food_value_filter = args.bonus_food_value
-                        .clone()
-                        .unwrap()
-                        .0;
-                    food_value_filter.contains(&f.into_iter().sum())
-                })
-            )
-            && (
-                args.container_slot_type.is_none()
-                || onelifedata_obj.slotStyle.as_ref().is_some_and(|ss| args.container_slot_type.as_ref().unwrap().contains(&ss))
-            )
-            // object isn't marked as removed
-            && !&twotech_obj.name.contains("removed")
-        })
-        .collect::<BTreeMap<_,_>>();
-
-    let elapsed = now.elapsed();
-    println!("Main object filtering took {} seconds", (elapsed.as_millis() as f32)/1000.0);
-    let now = Instant::now();
-
-    // Filter for objects that contain any set of other object IDs in its recipe (recursively)
-    if let Some(ingredient_sets_to_find) = ingredient_sets_to_find {
-        shared_objects = shared_objects.into_iter()
-            .filter(|(_, obj)| {
-                // Instead of just looking for the one target ID, we need to look for the all the values in each set.
-                // If any set has all its values matched, we have a match!
-                let mut found_match = false;
-                for ingredient_set in &ingredient_sets_to_find {
-                    // All ingredients must be present in recipe (actual check is "no ingredients may not be present")
-                    let ingredient_set_matches = !ingredient_set
-                        .iter()
-                        // Take each ID string and map it to a bool saying whether the object has this ID has an ingredient
-                        .map(|i| find_target_ingredient(obj, i.to_owned(), &initial_shared_objects))
-                        .collect::<Vec<_>>()
-                        .contains(&None);
-
-                    if ingredient_set_matches {
-                        // Immediately exit if we've found a match
-                        found_match = true;
-                        break;
-                    }
-                }
-                found_match
-            })
-            .collect::<BTreeMap<_,_>>();
-    }
-
-    // Filter for objects that DO NOT contain any set of other object IDs in its recipe (recursively)
-    // Item must not include ANY of these sets of ingredients in its recipe tree
-    if let Some(ingredient_sets_to_exclude) = ingredient_sets_to_exclude {
-        shared_objects = shared_objects.into_iter()
-            .filter(|(_, obj)| {
-                let mut an_ingredient_set_matches = false;
-                // If any ingredient set is present, we've found a match
-                for ingredient_set in &ingredient_sets_to_exclude {
-                    // All ingredients must be present for ingredient set to be a match
-                    let missing_an_ingredient = ingredient_set
-                        .iter()
-                        // Take each ID string and map it to a bool saying whether the object has this ID has an ingredient
-                        .map(|i| find_target_ingredient(obj, i.to_owned(), &initial_shared_objects))
-                        .collect::<Vec<_>>()
-                        .contains(&None);
-                    if !missing_an_ingredient {
-                        an_ingredient_set_matches = true;
-                        break;
-                    }
-                }
-                // We only want to keep objects that don't contain any of the ingredient sets in the query
-                !an_ingredient_set_matches
-            })
-            .collect::<BTreeMap<_,_>>();
-    }
+    let shared_objects = matching_objects(&game_database, &args, &object_filter);
 
     let elapsed = now.elapsed();
-    println!("Ingredient filtering took {} seconds", (elapsed.as_millis() as f32)/1000.0);
+    println!("Object filtering took {} seconds", (elapsed.as_millis() as f32)/1000.0);
     let now = Instant::now();
 
-    // Finally, sort the objects by their name, since it's the most human-friendly ordering
-    let shared_objects = shared_objects
-        .into_values()
-        .map(|v| (v.twotech_data.name.clone(), v))
-        .collect::<BTreeMap<_,_>>();
+    // Finally, sort (by name unless asked otherwise, since it's the most human-friendly ordering), limit and group the objects
+    let results = QueryResults::new(shared_objects.values(), &args.sort_by, args.group_by.as_deref(), args.limit);
 
     if args.generate_wiki_cards {
-        std::fs::write(&args.output_file, _generate_wiki_cards(&shared_objects)).context("Error generating wiki cards")?;
-    } else if args.wiki_table_output {
-        let wiki_output_data =
-        shared_objects
-            .iter()
-            .map(|(_, obj)| {
-                wiki_formats::_wiki_format_line_single_mover_type(obj)
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
+        std::fs::write(&args.output_file, _generate_wiki_cards(results.objects())).context("Error generating wiki cards")?;
+    } else if let Some(wiki_template) = &wiki_template {
+        let wiki_output_data = wiki_template.render_results(&results);
         std::fs::write(&args.output_file, wiki_output_data).context("Could not write to output file")?;
     } else {
-        // Serialize the object list to JSON and save to the output file location
-        let objects_as_string = serde_json::to_string(&shared_objects).context("Could not serialize SharedGameObject to String")?;
+        // Serialize the object list in the requested format and save to the output file location
+        let objects_as_string = format_objects(&results, args.format, &args.fields, args.json_list)?;
         std::fs::write(&args.output_file, objects_as_string).context("Could not write to output file")?;
     }
-    println!("Wrote {} matching objects' data to output file at {}", shared_objects.len(), args.output_file);
+    println!("Wrote {} matching objects' data to output file at {}", results.len(), args.output_file);
     let elapsed = now.elapsed();
     println!("Sorting and output took {} seconds", (elapsed.as_millis() as f32)/1000.0);
     let elapsed = start.elapsed();
     println!("Entire program took {:.2} seconds", (elapsed.as_millis() as f32)/1000.0);
-    let now = Instant::now();
 
     Ok(())
 }
 
-fn find_target_ingredient<'a>(root_obj: &'a SharedGameObject, target_id: String, object_database: &'a BTreeMap<String, SharedGameObject>) -> Option<&'a SharedGameObject> {
-    let mut stack = Vec::new();
-    let mut visited = HashSet::new();
-    stack.push(root_obj);
-    while let Some(obj) = stack.pop() {
-        // If object has no ID or has already been visited, skip it
-        let obj_id = &obj.twotech_data.id;
-        if visited.contains(obj_id) {
-            continue;
+fn run_command(command: &Command, args: &Args) -> Result<()> {
+    match command {
+        Command::VerifyRoundtrip => {
+            let report = verify_object_roundtrip(&args.one_life_data_directory)?;
+            for mismatch in &report.mismatches {
+                println!("{}\n", mismatch.diff);
+            }
+            for parse_failure in &report.parse_failures {
+                println!("Could not parse {parse_failure}");
+            }
+            println!(
+                "Checked {} object files: {} round-tripped exactly, {} differed, {} could not be parsed",
+                report.files_checked,
+                report.files_checked - report.mismatches.len() - report.parse_failures.len(),
+                report.mismatches.len(),
+                report.parse_failures.len(),
+            );
         }
-        // If current object is the ID we're looking for, return true!
-        if obj_id == &target_id {
-            return Some(obj);
+        Command::UsedIn { object, max_depth } => {
+            let game_database = GameDatabase::load(&args.load_options())?;
+            let used_in = game_database.used_in(object, *max_depth).context(format!("No object found matching {object}"))?;
+            let object_name = |id: &str| game_database.get(id).map_or(id.to_string(), |o| o.twotech_data.name.clone());
+            let mut current_depth = 0;
+            for used_in in &used_in {
+                if used_in.depth != current_depth {
+                    current_depth = used_in.depth;
+                    println!("Depth {current_depth}:");
+                }
+                let path = used_in.path.iter().map(|id| object_name(id)).collect::<Vec<_>>().join(" -> ");
+                println!("  {} ({}): {path}", object_name(&used_in.product_id), used_in.product_id);
+            }
+            println!("{object} is used in {} craftable objects", used_in.len());
         }
-        // println!("New Total: {} after adding object ID to visited: {obj_id}", visited.len());
-        visited.insert(obj_id);
-
-        let obj_recipe = match &obj.twotech_data.recipe {
-            Some(recipe) => recipe,
-            None => continue,
-        };
-
-        // Check each ingredient for being the target_id, and if we haven't yet visited the ingredient, push it to the list
-        if let Some(ingredients) = obj_recipe.ingredients.as_ref().map(|ivec| HashSet::<&String>::from_iter(ivec.iter())) {
-            for ingredient in ingredients {
-                if ingredient == &target_id {
-                    return Some(obj);
+        Command::SoundUsage { sound_ids } => {
+            let game_database = GameDatabase::load(&args.load_options())?;
+            for (sound_id, users) in game_database.sound_usage() {
+                if !sound_ids.is_empty() && !sound_ids.contains(&sound_id) {
+                    continue;
                 }
-                if !visited.contains(ingredient) {
-                    // if let Some(ingredient_object) = get_object_by_id(&ingredient, object_database) {
-                    if let Some(ingredient_object) = object_database.get(ingredient) {
-                        stack.push(ingredient_object);
-                    }
+                println!("Sound {sound_id} ({} uses):", users.len());
+                for (object_id, group) in users {
+                    let name = game_database.get(&object_id).map_or("", |o| o.twotech_data.name.as_str());
+                    println!("  {object_id} {name} ({})", group.to_string());
                 }
             }
         }
-
-        // Push onto our stack all the unique values in the object recipe that we haven't yet visited
-        obj_recipe.steps
-        .as_ref()
-        .unwrap_or(&Vec::default())
-        .into_iter()
-        .flatten()
-        .flat_map(|rs| [rs.actorID.clone().unwrap_or("-1".to_string()), rs.targetID.clone().unwrap_or("-1".to_string())])
-        .filter(|ingredient| !visited.contains(ingredient))
-        .filter_map(|ingredient| object_database.get(&ingredient))
-        .for_each(|recipe_ingredient_object| stack.push(recipe_ingredient_object));
-
-        if obj.twotech_data.recipe.as_ref().is_some_and(|r|
-            r.ingredients.as_ref()
-            .map(|ingredients| HashSet::<&String>::from_iter(ingredients.iter()))
-            .is_some_and(|ingredients| {
-                // Now we have a list of all the unique items in the ingredients list.
-                // We need to check if any of them are the target_id
-                // We then need to check if we've already visited each ingredient, and if not, push it to the stack
-                for ingredient in ingredients {
-                    // println!("Checking ingredient {ingredient}");
-                    if ingredient == &target_id {
-                        println!("Ingredient {ingredient} matched!");
-                        return true;
-                    }
-                    if !visited.contains(ingredient) {
-                        stack.push(object_database.get(ingredient).context("Could not get ingredient from object database...").unwrap());
+        Command::Bom { object, quantity, json } => {
+            let game_database = GameDatabase::load(&args.load_options())?;
+            let bill_of_materials = BillOfMaterials::new(&game_database, object, *quantity)?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&bill_of_materials)?);
+            } else {
+                println!("{}", bill_of_materials.to_string());
+            }
+        }
+        Command::Reachable { objects, max_steps, natural, json } => {
+            let game_database = GameDatabase::load(&args.load_options())?;
+            let reachable = ReachableObjects::new(&game_database, objects, *max_steps, *natural)?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&reachable)?);
+                return Ok(());
+            }
+            let object_name = |id: &str| game_database.get(id).map_or(id.to_string(), |o| format!("{} ({id})", o.twotech_data.name));
+            let mut current_steps = 0;
+            for reached in &reachable.reached {
+                if reached.steps != current_steps {
+                    current_steps = reached.steps;
+                    println!("Step {current_steps}:");
+                }
+                let actor = reached.actor_id.as_deref().map_or("bare hand".to_string(), object_name);
+                let target = reached.target_id.as_deref().map_or("the ground".to_string(), object_name);
+                let via = if reached.timed {
+                    format!("{target} decays after {}", reached.decay.as_ref().map_or("a while".to_string(), DecayTime::to_string))
+                } else {
+                    format!("{actor} on {target}")
+                };
+                println!("  {} ({}): {via}", reached.name, reached.id);
+            }
+            let natural = if *natural { " and natural objects" } else { "" };
+            println!("{} objects can be made from {}{natural}", reachable.reached.len(), objects.join(", "));
+        }
+        Command::Path { from, to, by, json } => {
+            let game_database = GameDatabase::load(&args.load_options())?;
+            let Some(path) = TransitionPath::find(&game_database, from, to, *by)? else {
+                println!("There is no way to turn {from} into {to}");
+                return Ok(());
+            };
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&path)?);
+                return Ok(());
+            }
+            let object_name = |id: &Option<String>, nothing: &str| {
+                id.as_deref().map_or(nothing.to_string(), |id| game_database.get(id).map_or(id.to_string(), |o| format!("{} ({id})", o.twotech_data.name)))
+            };
+            for (i, step) in path.steps.iter().enumerate() {
+                let results = format!("{} + {}", object_name(&step.new_actor_id, "nothing"), object_name(&step.new_target_id, "nothing"));
+                if step.timed {
+                    let decay = step.decay.as_ref().map_or("a while".to_string(), DecayTime::to_string);
+                    let target = object_name(&step.target_id, "nothing");
+                    println!("{}. Wait {decay} for {target} to decay -> {}", i + 1, object_name(&step.new_target_id, "nothing"));
+                } else {
+                    let actor = object_name(&step.actor_id, "bare hand");
+                    println!("{}. Use {actor} on {} -> {results}", i + 1, object_name(&step.target_id, "the ground"));
+                }
+            }
+            println!("{} steps, {} seconds waiting on decays", path.steps.len(), path.wait_seconds);
+        }
+        Command::Biomes { biomes } => {
+            let game_database = GameDatabase::load(&args.load_options())?;
+            for (biome, objects) in game_database.natural_objects_by_biome() {
+                if !biomes.is_empty() && !biomes.contains(&biome) {
+                    continue;
+                }
+                println!("{} ({}), {} natural objects:", biome.to_string(), biome.to_u8(), objects.len());
+                for (object_id, spawn_chance) in objects {
+                    let name = game_database.get(&object_id).map_or("", |o| o.twotech_data.name.as_str());
+                    println!("  {spawn_chance:>10.6}  {object_id} {name}");
+                }
+            }
+        }
+        Command::Outfit { maximize, insulation_weight, slots_weight, max_depth, json } => {
+            let game_database = GameDatabase::load(&args.load_options())?;
+            let goal = match maximize {
+                OutfitGoal::Weighted { .. } => OutfitGoal::Weighted { insulation_weight: *insulation_weight, slots_weight: *slots_weight },
+                goal => *goal,
+            };
+            let candidates = matching_objects(&game_database, args, &args.object_filter()?);
+            let candidates = candidates
+                .values()
+                .filter(|o| max_depth.is_none_or(|max_depth| o.twotech_data.depth.unwrap_or(0) <= max_depth));
+            let outfit = Outfit::best(candidates, goal);
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&outfit)?);
+            } else {
+                println!("{}", outfit.to_string());
+            }
+        }
+        Command::CheckInsulation => {
+            let game_database = GameDatabase::load(&args.load_options())?;
+            let mut mismatches = 0;
+            for (id, shared_obj) in &game_database.objects {
+                let Some((computed, twotech)) = shared_obj.insulation_mismatch() else {
+                    continue;
+                };
+                let rvalue = shared_obj.one_life_game_data.rValue.unwrap_or(0.0);
+                let clothing = shared_obj.one_life_game_data.clothing.as_ref().map_or("n".to_string(), |c| c.clothing.to_string());
+                println!(
+                    "{id} {}: computed {computed:.4} (rValue {rvalue} x clothing {clothing}), twotech says {twotech:.4}",
+                    shared_obj.twotech_data.name,
+                );
+                mismatches += 1;
+            }
+            println!("{mismatches} of {} objects have insulation that disagrees with twotech", game_database.objects.len());
+        }
+        Command::FoodReport { top, json } => {
+            let game_database = GameDatabase::load(&args.load_options())?;
+            let food_report = FoodReport::new(&game_database);
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&food_report)?);
+                return Ok(());
+            }
+            println!("{:>9} {:>5} {:>8} {:>5} {:>5} {:>5} {:>4}  Object", "Immediate", "Bonus", "Servings", "Total", "Depth", "Steps", "Raw");
+            for food in &food_report.foods {
+                let depth = food.depth.map_or("-".to_string(), |depth| depth.to_string());
+                println!(
+                    "{:>9} {:>5} {:>8} {:>5} {:>5} {:>5} {:>4}  {} ({})",
+                    food.immediate, food.bonus, food.servings, food.total, depth, food.steps, food.raw_ingredients, food.name, food.id,
+                );
+            }
+            let print_ranking = |heading: &str, ranking: &[String], efficiency: fn(&FoodEntry) -> f64| {
+                println!("\n{heading}:");
+                for (rank, food) in ranking.iter().filter_map(|id| food_report.get(id)).take(*top).enumerate() {
+                    println!("{:>4}. {:>7.2}  {} ({})", rank + 1, efficiency(food), food.name, food.id);
+                }
+            };
+            print_ranking("Food per recipe step", &food_report.by_food_per_step, |food| food.food_per_step);
+            print_ranking("Food per raw ingredient", &food_report.by_food_per_raw_ingredient, |food| food.food_per_raw_ingredient);
+        }
+        Command::Creatures { json } => {
+            let game_database = GameDatabase::load(&args.load_options())?;
+            let creatures = creatures(&game_database);
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&creatures)?);
+                return Ok(());
+            }
+            let object_name = |id: &Option<String>, nothing: &str| {
+                id.as_deref().map_or(nothing.to_string(), |id| game_database.get(id).map_or(id.to_string(), |o| format!("{} ({id})", o.twotech_data.name)))
+            };
+            println!("{:<8} {:>8} {:>6} {:>5}  {:<30} Biomes", "Move", "Distance", "Deadly", "Speed", "Creature");
+            for creature in &creatures {
+                let optional = |value: Option<String>| value.unwrap_or("-".to_string());
+                let biomes = creature.biomes.iter().map(|biome| biome.to_string()).collect::<Vec<_>>().join(", ");
+                println!(
+                    "{:<8} {:>8} {:>6} {:>5}  {:<30} {biomes}",
+                    optional(creature.move_type.as_ref().map(|move_type| move_type.to_string())),
+                    optional(creature.move_distance.map(|distance| distance.to_string())),
+                    creature.deadly_distance,
+                    optional(creature.speed_mult.map(|speed| speed.to_string())),
+                    format!("{} ({})", creature.name, creature.id),
+                );
+                for (verb, transitions) in [("Tame", &creature.tame), ("Kill", &creature.kill), ("Butcher", &creature.butcher), ("Other", &creature.other)] {
+                    for transition in transitions {
+                        println!(
+                            "    {verb}: {} on {} -> {} + {}",
+                            object_name(&transition.actor_id, "bare hand"),
+                            object_name(&transition.target_id, "the ground"),
+                            object_name(&transition.new_actor_id, "nothing"),
+                            object_name(&transition.new_target_id, "nothing"),
+                        );
                     }
                 }
-                // If none of the ingredients contained the target_id, return false
-                return false;
-            })
-        ) {
-            return Some(obj);
+            }
         }
+        Command::RecipeWait { object, json } => {
+            let game_database = GameDatabase::load(&args.load_options())?;
+            let recipe_wait = RecipeWait::new(&game_database, object)?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&recipe_wait)?);
+                return Ok(());
+            }
+            let object_name = |id: &str| game_database.get(id).map_or(id.to_string(), |o| format!("{} ({id})", o.twotech_data.name));
+            for wait in &recipe_wait.waits {
+                println!("  {:>6}  {} -> {}", wait.decay.to_string(), object_name(&wait.target_id), object_name(&wait.product_id));
+            }
+            println!(
+                "Making {} ({}) means waiting {} on {} decays",
+                recipe_wait.name,
+                recipe_wait.object_id,
+                DecayTime::Seconds(recipe_wait.total_seconds).to_string(),
+                recipe_wait.waits.len(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Every object matching the filtering options (built into `object_filter`), including the ingredient filters
+fn matching_objects(game_database: &GameDatabase, args: &Args, object_filter: &ObjectFilter) -> BTreeMap<String, SharedGameObject> {
+    let mut shared_objects = game_database.filter_objects(object_filter);
+    // Filter for objects that contain any set of other object IDs in its recipe (recursively)
+    if let Some(ingredient_sets) = &args.with_ingredients {
+        shared_objects = game_database.filter_with_ingredients(shared_objects, &game_database.resolve_ingredient_sets(ingredient_sets));
     }
-    return None;
+    // Filter for objects that DO NOT contain any set of other object IDs in its recipe (recursively)
+    // Item must not include ANY of these sets of ingredients in its recipe tree
+    if let Some(ingredient_sets) = &args.without_ingredients {
+        shared_objects = game_database.filter_without_ingredients(shared_objects, &game_database.resolve_ingredient_sets(ingredient_sets));
+    }
+    shared_objects
 }
 
 fn pause(message: Option<String>) -> bool {
@@ -614,7 +612,7 @@ fn pause(message: Option<String>) -> bool {
 
     // Always print out the default message, explaining how to continue or exit.
     let default_message = "Type y or yes and ENTER to continue, anything else to exit: ";
-    stdout.write(default_message.as_bytes()).unwrap();
+    stdout.write_all(default_message.as_bytes()).unwrap();
     stdout.flush().unwrap();
 
     // We want to save the string entered by the user.
@@ -623,58 +621,12 @@ fn pause(message: Option<String>) -> bool {
     // Look at what the user typed and only return true if they types "y" or "yes", where any/all letters can be uppercase or lowercase
     let _bytes_read = stdin.read_line(&mut stdin_data).unwrap();
     let trimmed_stdin_data = stdin_data.trim();
-    if trimmed_stdin_data.len() > 0 {
+    if !trimmed_stdin_data.is_empty() {
         let stdin_data = trimmed_stdin_data.to_lowercase();
         let stdin_data_str = stdin_data.as_str();
         if stdin_data_str == "y" || stdin_data_str == "yes" {
             return true;
         }
     }
-    return false;
-}
-
-#[derive(Clone, Debug)]
-pub struct I32Range(RangeInclusive<i32>);
-
-impl FromStr for I32Range {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split("..").collect();
-        match parts.len() {
-            1 => {
-                let start: i32 = parts[0].parse().map_err(|_| "Invalid number").map_err(|e| anyhow!(e))?;
-                Ok(I32Range(start..=start))
-            },
-            2 => {
-                let start: i32 = if parts[0].is_empty() { 0 } else { parts[0].parse().map_err(|_| "Invalid number").map_err(|e| anyhow!(e))? };
-                let end: i32 = if parts[1].is_empty() { i32::MAX } else { parts[1].parse().map_err(|_| "Invalid number").map_err(|e| anyhow!(e))? };
-                Ok(I32Range(start..=end))
-            },
-            _ => Err(anyhow!("Invalid range format")),
-        }
-    }
-}
-
-#[derive(Clone, Debug)]
-pub struct F32Range(RangeInclusive<f32>);
-
-impl FromStr for F32Range {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split("..").collect();
-        match parts.len() {
-            1 => {
-                let start: f32 = parts[0].parse().map_err(|_| "Invalid number").map_err(|e| anyhow!(e))?;
-                Ok(F32Range(start..=start))
-            },
-            2 => {
-                let start: f32 = if parts[0].is_empty() { 0.0 } else { parts[0].parse().map_err(|_| "Invalid number").map_err(|e| anyhow!(e))? };
-                let end: f32 = if parts[1].is_empty() { f32::MAX } else { parts[1].parse().map_err(|_| "Invalid number").map_err(|e| anyhow!(e))? };
-                Ok(F32Range(start..=end))
-            },
-            _ => Err(anyhow!("Invalid range format")),
-        }
-    }
+    false
 }
@@ -0,0 +1,193 @@
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use strum::IntoEnumIterator;
+
+use crate::filter_expression::FilterExpression;
+use crate::one_life_data_object::{BiomeId, SlotStyle, SoundGroup};
+use crate::twotech_object::{ClothingType, DecayTime, MoveType};
+use crate::SharedGameObject;
+
+/// All the criteria an object must meet to be kept by `GameDatabase::filter_objects`.
+/// Every criterion is ANDed together, and an empty/None criterion matches everything.
+#[derive(Clone, Debug, Default)]
+pub struct ObjectFilter {
+    pub clothing: Vec<ClothingType>,
+    pub move_types: Vec<MoveType>,
+    pub min_pickup_age: i32,
+    pub slot_size: Option<F32Range>,
+    pub num_slots: Option<I32Range>,
+    // If is_food is None, no filter. If Some(), either filter for food (true), or non-food (false)
+    pub is_food: Option<bool>,
+    pub immediate_food_value: Option<I32Range>,
+    pub bonus_food_value: Option<I32Range>,
+    pub total_food_value: Option<I32Range>,
+    pub container_slot_type: Option<Vec<SlotStyle>>,
+    // Objects that play any of these sound IDs
+    pub uses_sound: Vec<i32>,
+    // Objects that have no sound at all in any of these groups
+    pub without_sound: Vec<SoundGroup>,
+    // Objects that decay on their own within this long
+    pub decays_within: Option<DecayTime>,
+    // Objects that last at least this long when left alone, including ones that never decay
+    pub min_decay: Option<DecayTime>,
+    // Objects that spawn naturally in any of these biomes
+    pub biomes: Vec<BiomeId>,
+    // Free-form `--where` expression, ANDed with everything else
+    pub expression: Option<FilterExpression>,
+}
+
+impl ObjectFilter {
+    pub fn matches(&self, shared_obj: &SharedGameObject) -> bool {
+        let onelifedata_obj = &shared_obj.one_life_game_data;
+        let twotech_obj = &shared_obj.twotech_data;
+        // numSlots filter. Default is all values > 0
+        let num_slots_filter = self.num_slots
+            .clone()
+            .unwrap_or(I32Range(RangeInclusive::new(0, i32::MAX)))
+            .0;
+        // slotSize filter. Default is all values
+        let slot_size_filter = self.slot_size
+            .clone()
+            .unwrap_or(F32Range(RangeInclusive::new(f32::MIN, f32::MAX)))
+            .0;
+
+        twotech_obj.craftable.unwrap_or(false)
+        // Specific type of clothing
+        && (
+            self.clothing.is_empty()
+            || twotech_obj.clothing.as_ref().is_some_and(|clothing| self.clothing.contains(clothing))
+        )
+        && (
+            self.move_types.is_empty()
+            // Objects without a moveType don't move
+            || self.move_types.contains(twotech_obj.moveType.as_ref().unwrap_or(&MoveType::None))
+        )
+        // Is over minimum pickup age filter (0 if not specified)
+        && twotech_obj.minPickupAge.unwrap_or(0) >= self.min_pickup_age
+        // Number of slots for item falls within specified range (default is all positive values)
+        && num_slots_filter.contains(&twotech_obj.numSlots.unwrap_or(0))
+        // slotSize is for item falls within specified range (default is all values allowed)
+        && slot_size_filter.contains(&twotech_obj.slotSize.unwrap_or(f32::MIN))
+        // User either wants to filter for items being food or not food, or is_food will be None
+        && self.is_food.is_none_or(|is_food| {
+            twotech_obj.foodValue.as_ref().is_some_and(|f| !f.is_empty()) == is_food
+        })
+        // Total food supplied by the item, including immediate food and bonus
+        && self.total_food_value.as_ref().is_none_or(|food_value_filter| {
+            twotech_obj.foodValue.as_ref().is_some_and(|f| food_value_filter.0.contains(&f.iter().sum()))
+        })
+        // Immediate food supplied by the item (the first foodValue)
+        && self.immediate_food_value.as_ref().is_none_or(|food_value_filter| {
+            twotech_obj.foodValue.as_ref().is_some_and(|f| food_value_filter.0.contains(&f.first().copied().unwrap_or(0)))
+        })
+        // Bonus food supplied by the item (the second foodValue)
+        && self.bonus_food_value.as_ref().is_none_or(|food_value_filter| {
+            twotech_obj.foodValue.as_ref().is_some_and(|f| food_value_filter.0.contains(&f.get(1).copied().unwrap_or(0)))
+        })
+        && self.container_slot_type.as_ref().is_none_or(|container_slot_type| {
+            onelifedata_obj.slotStyle.as_ref().is_some_and(|ss| container_slot_type.contains(ss))
+        })
+        && (
+            self.uses_sound.is_empty()
+            || onelifedata_obj.sounds.as_ref().is_some_and(|sounds| self.uses_sound.iter().any(|&id| sounds.uses_sound(id)))
+        )
+        && self.without_sound.iter().all(|&group| {
+            onelifedata_obj.sounds.as_ref().is_none_or(|sounds| !sounds.has_sound(group))
+        })
+        && self.decays_within.as_ref().is_none_or(|decays_within| &twotech_obj.decay_time() <= decays_within)
+        && self.min_decay.as_ref().is_none_or(|min_decay| &twotech_obj.decay_time() >= min_decay)
+        && (
+            self.biomes.is_empty()
+            || shared_obj.natural_biomes().iter().any(|(biome, _)| self.biomes.contains(biome))
+        )
+        // object isn't marked as removed
+        && !twotech_obj.name.contains("removed")
+        && self.expression.as_ref().is_none_or(|expression| expression.matches(shared_obj))
+    }
+}
+
+/// Parse a comma-separated list of clothing types, e.g. "t,b,Shoe"
+pub fn parse_clothing_types(s: &str) -> Result<Vec<ClothingType>> {
+    s.split(',')
+        .map(ClothingType::from_str)
+        .collect()
+}
+
+/// Parse a comma-separated list of move types.
+/// "all" selects every move type, and "not_none"/"notnone" selects every move type except None.
+pub fn parse_move_types(s: &str) -> Result<Vec<MoveType>> {
+    let move_type_arg_parts = s.split(',').collect::<Vec<_>>();
+    if move_type_arg_parts.iter().any(|p| p.to_lowercase().as_str() == "all") {
+        Ok(MoveType::iter().collect())
+    } else if move_type_arg_parts.iter().any(|p| {
+        p.to_lowercase().replace(['_', ' '], "").as_str() == "notnone"
+    }) {
+        Ok(MoveType::iter().filter(|move_type| move_type != &MoveType::None).collect())
+    } else {
+        move_type_arg_parts
+            .iter()
+            .map(|move_type| {
+                MoveType::from_str(move_type).map_err(|_| anyhow!("Could not parse argument string into MoveType: {move_type}"))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IngredientSet(pub Vec<String>);
+
+impl FromStr for IngredientSet {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(IngredientSet(s.split(',').map(|s| s.to_string()).collect::<Vec<String>>()))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct I32Range(pub RangeInclusive<i32>);
+
+impl FromStr for I32Range {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split("..").collect();
+        match parts.len() {
+            1 => {
+                let start: i32 = parts[0].parse().map_err(|_| "Invalid number").map_err(|e| anyhow!(e))?;
+                Ok(I32Range(start..=start))
+            },
+            2 => {
+                let start: i32 = if parts[0].is_empty() { 0 } else { parts[0].parse().map_err(|_| "Invalid number").map_err(|e| anyhow!(e))? };
+                let end: i32 = if parts[1].is_empty() { i32::MAX } else { parts[1].parse().map_err(|_| "Invalid number").map_err(|e| anyhow!(e))? };
+                Ok(I32Range(start..=end))
+            },
+            _ => Err(anyhow!("Invalid range format")),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct F32Range(pub RangeInclusive<f32>);
+
+impl FromStr for F32Range {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split("..").collect();
+        match parts.len() {
+            1 => {
+                let start: f32 = parts[0].parse().map_err(|_| "Invalid number").map_err(|e| anyhow!(e))?;
+                Ok(F32Range(start..=start))
+            },
+            2 => {
+                let start: f32 = if parts[0].is_empty() { 0.0 } else { parts[0].parse().map_err(|_| "Invalid number").map_err(|e| anyhow!(e))? };
+                let end: f32 = if parts[1].is_empty() { f32::MAX } else { parts[1].parse().map_err(|_| "Invalid number").map_err(|e| anyhow!(e))? };
+                Ok(F32Range(start..=end))
+            },
+            _ => Err(anyhow!("Invalid range format")),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+#![allow(non_snake_case)]
+#![allow(clippy::to_string_trait_impl)]
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// A OneLifeData7 category, parsed from categories/N.txt
+///
+/// Categories let one generic object (the parent, e.g. "@ Any Berry Bowl") stand in for a list of member objects.
+/// A file looks something like this, where the pattern/probSet line is optional:
+/// parentID=1234
+/// probSet
+/// numObjects=2
+/// 100 0.750000
+/// 101 0.250000
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CategoryData {
+    pub parentID: i32,
+    // Pattern categories are matched up member-by-member with other pattern categories in the same transition
+    pub pattern: bool,
+    // Probability sets pick one member at random, using the member weights
+    pub probSet: bool,
+    pub numObjects: i32,
+    pub objectIDs: Vec<i32>,
+    // Only present for probability sets
+    pub objectWeights: Option<Vec<f32>>,
+}
+
+/// One concrete object a category expands to, with the chance of it being picked
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CategoryMember {
+    pub id: i32,
+    pub probability: f32,
+}
+
+impl CategoryData {
+    /// The category's members, with probabilities from the member weights for probability sets, or a uniform chance otherwise
+    pub fn members(&self) -> Vec<CategoryMember> {
+        if self.objectIDs.is_empty() {
+            return Vec::new();
+        }
+        match &self.objectWeights {
+            Some(weights) => {
+                let total_weight = weights.iter().sum::<f32>();
+                self.objectIDs
+                    .iter()
+                    .zip(weights)
+                    .map(|(&id, &weight)| CategoryMember {
+                        id,
+                        probability: if total_weight > 0.0 { weight / total_weight } else { 0.0 },
+                    })
+                    .collect()
+            }
+            None => {
+                let probability = 1.0 / self.objectIDs.len() as f32;
+                self.objectIDs
+                    .iter()
+                    .map(|&id| CategoryMember { id, probability })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl ToString for CategoryData {
+    fn to_string(&self) -> String {
+        let mut output = Vec::new();
+        output.push(format!("parentID={}", self.parentID));
+        if self.pattern {
+            output.push("pattern".to_string());
+        }
+        if self.probSet {
+            output.push("probSet".to_string());
+        }
+        output.push(format!("numObjects={}", self.numObjects));
+        match &self.objectWeights {
+            Some(weights) => {
+                for (id, weight) in self.objectIDs.iter().zip(weights) {
+                    output.push(format!("{} {:.6}", id, weight));
+                }
+            }
+            None => {
+                for id in &self.objectIDs {
+                    output.push(id.to_string());
+                }
+            }
+        }
+        output.join("\n")
+    }
+}
+
+impl FromStr for CategoryData {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parentID = None;
+        let mut pattern = false;
+        let mut probSet = false;
+        let mut numObjects = None;
+        let mut objectIDs = Vec::new();
+        let mut objectWeights = Vec::new();
+        for line in s.trim().lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match line.split_once('=') {
+                Some(("parentID", value)) => parentID = Some(value.parse::<i32>().context("Invalid parentID value")?),
+                Some(("numObjects", value)) => numObjects = Some(value.parse::<i32>().context("Invalid numObjects value")?),
+                Some((variable_name, _)) => {
+                    log::info!("CategoryData::FromStr: Unexpected variable name {}", variable_name);
+                }
+                None if line == "pattern" => pattern = true,
+                None if line == "probSet" => probSet = true,
+                None => {
+                    // Member lines are either "id" or, for probability sets, "id weight"
+                    let mut member_sections = line.split_whitespace();
+                    let id = member_sections
+                        .next()
+                        .unwrap_or_default()
+                        .parse::<i32>()
+                        .context(format!("Invalid category member line \"{line}\""))?;
+                    objectIDs.push(id);
+                    if let Some(weight) = member_sections.next() {
+                        objectWeights.push(weight.parse::<f32>().context(format!("Invalid category member weight \"{line}\""))?);
+                    }
+                }
+            }
+        }
+        let parentID = parentID.ok_or_else(|| anyhow!("Missing required value for \"parentID\""))?;
+        let numObjects = numObjects.ok_or_else(|| anyhow!("Missing required value for \"numObjects\""))?;
+        Ok(CategoryData {
+            parentID,
+            pattern,
+            probSet,
+            numObjects,
+            objectIDs,
+            objectWeights: if objectWeights.is_empty() { None } else { Some(objectWeights) },
+        })
+    }
+}
+
+/// Expand a category ID into its concrete members.
+/// Members that are themselves categories are expanded too, with their probabilities multiplied through.
+/// An ID that isn't a category expands to just itself.
+pub fn expand_category(categories: &BTreeMap<i32, CategoryData>, id: i32) -> Vec<CategoryMember> {
+    let mut members = Vec::new();
+    expand_category_into(categories, id, 1.0, &mut vec![], &mut members);
+    members
+}
+
+fn expand_category_into(categories: &BTreeMap<i32, CategoryData>, id: i32, probability: f32, ancestors: &mut Vec<i32>, members: &mut Vec<CategoryMember>) {
+    let category = match categories.get(&id) {
+        Some(category) if !ancestors.contains(&id) => category,
+        _ => {
+            match members.iter_mut().find(|m| m.id == id) {
+                Some(member) => member.probability += probability,
+                None => members.push(CategoryMember { id, probability }),
+            }
+            return;
+        }
+    };
+    ancestors.push(id);
+    for member in category.members() {
+        expand_category_into(categories, member.id, probability * member.probability, ancestors, members);
+    }
+    ancestors.pop();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categories(files: &[&str]) -> BTreeMap<i32, CategoryData> {
+        files
+            .iter()
+            .map(|file| file.parse::<CategoryData>().unwrap())
+            .map(|category| (category.parentID, category))
+            .collect()
+    }
+
+    fn expand(categories: &BTreeMap<i32, CategoryData>, id: i32) -> Vec<(i32, f32)> {
+        expand_category(categories, id).into_iter().map(|member| (member.id, member.probability)).collect()
+    }
+
+    #[test]
+    fn plain_category_members_are_equally_likely() {
+        let categories = categories(&["parentID=100\nnumObjects=4\n1\n2\n3\n4"]);
+        assert_eq!(expand(&categories, 100), [(1, 0.25), (2, 0.25), (3, 0.25), (4, 0.25)]);
+    }
+
+    #[test]
+    fn probability_set_uses_the_weights() {
+        let categories = categories(&["parentID=100\nprobSet\nnumObjects=2\n1 0.600000\n2 0.200000"]);
+        assert_eq!(expand(&categories, 100), [(1, 0.75), (2, 0.25)]);
+    }
+
+    #[test]
+    fn nested_categories_multiply_probabilities() {
+        let categories = categories(&["parentID=100\nnumObjects=2\n1\n200", "parentID=200\nnumObjects=2\n2\n3"]);
+        assert_eq!(expand(&categories, 100), [(1, 0.5), (2, 0.25), (3, 0.25)]);
+    }
+
+    #[test]
+    fn members_reached_more_than_once_are_merged() {
+        let categories = categories(&["parentID=100\nnumObjects=2\n1\n200", "parentID=200\nnumObjects=2\n1\n2"]);
+        assert_eq!(expand(&categories, 100), [(1, 0.75), (2, 0.25)]);
+    }
+
+    #[test]
+    fn self_referencing_category_stops_at_itself() {
+        let contains_itself = categories(&["parentID=100\nnumObjects=2\n100\n1"]);
+        assert_eq!(expand(&contains_itself, 100), [(100, 0.5), (1, 0.5)]);
+        // 100 contains 200, which contains 100 again
+        let cycle = categories(&["parentID=100\nnumObjects=1\n200", "parentID=200\nnumObjects=2\n100\n1"]);
+        assert_eq!(expand(&cycle, 100), [(100, 0.5), (1, 0.5)]);
+    }
+
+    #[test]
+    fn objects_that_arent_categories_expand_to_themselves() {
+        assert_eq!(expand(&BTreeMap::new(), 7), [(7, 1.0)]);
+    }
+}
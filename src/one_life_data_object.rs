@@ -1,7 +1,12 @@
 #![allow(non_snake_case)]
+#![allow(clippy::to_string_trait_impl)]
 use anyhow::{anyhow, Result};
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
 use std::str::FromStr;
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::parse_error::ParseError;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct BlocksWalkingData {
@@ -34,10 +39,7 @@ impl FromStr for BlocksWalkingData {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let variable_sections = s.trim().split(',').collect::<Vec<_>>();
         // First section is blocksWalking. Beyond that, we deal with whatever supported values are present
-        let blocksWalking = variable_sections[0]
-        .split('=')
-        .collect::<Vec<_>>()
-        [1]
+        let blocksWalking = section_value(variable_sections[0])?
         .parse::<i8>()?
         .to_bool();
         let mut leftBlockingRadius = None;
@@ -46,9 +48,9 @@ impl FromStr for BlocksWalkingData {
         for &variable_section in variable_sections.iter().skip(1) {
             let variable_data = variable_section.split('=').collect::<Vec<_>>();
             match variable_data[0] {
-                "leftBlockingRadius" => leftBlockingRadius = Some(variable_data[1].parse()?),
-                "rightBlockingRadius" => rightBlockingRadius = Some(variable_data[1].parse()?),
-                "drawBehindPlayer" => drawBehindPlayer = Some(variable_data[1].parse::<i8>()?.to_bool()),
+                "leftBlockingRadius" => leftBlockingRadius = Some(section_value(variable_section)?.parse()?),
+                "rightBlockingRadius" => rightBlockingRadius = Some(section_value(variable_section)?.parse()?),
+                "drawBehindPlayer" => drawBehindPlayer = Some(section_value(variable_section)?.parse::<i8>()?.to_bool()),
                 _ => {
                     log::info!("BlocksWalkingData::FromStr: Unexpected variable name {}", variable_data[0]);
                 }
@@ -67,18 +69,7 @@ impl FromStr for BlocksWalkingData {
 pub struct MapChanceData {
     // line looks something like this: mapChance=0.000000#biomes_0,1,2,3
     pub mapChance: f32,
-    // Biome IDs, as of data version 426, are as follows:
-    // 0 => Grasslands
-    // 1 => Swamp
-    // 2 => Yellow Prairies
-    // 3 => Badlands
-    // 4 => Tundra
-    // 5 => Desert
-    // 6 => Jungle
-    // 7 => Deep Water
-    // 8 => Flower Fields
-    // 9 => Shallow Water
-    pub biomes: Option<Vec<u8>>
+    pub biomes: Option<Vec<BiomeId>>
 }
 
 impl ToString for MapChanceData {
@@ -87,8 +78,7 @@ impl ToString for MapChanceData {
         output.push_str(&format!("mapChance={:.6}", self.mapChance));
         if let Some(biomes) = &self.biomes {
             output.push_str("#biomes_");
-            biomes.iter().for_each(|&biome| output.push_str(&format!("{biome},")));
-            output.pop();
+            output.push_str(&biomes.iter().map(|biome| biome.to_u8().to_string()).collect::<Vec<_>>().join(","));
         }
         output
     }
@@ -100,12 +90,20 @@ impl FromStr for MapChanceData {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let variable_sections = s.trim().split('#').collect::<Vec<_>>();
         // First section is blocksWalking. Beyond that, we deal with whatever supported values are present
-        let mapChance = variable_sections[0]
-        .split('=')
-        .collect::<Vec<_>>()
-        [1]
+        let mapChance = section_value(variable_sections[0])?
         .parse()?;
-        let biomes = Some(variable_sections[1][7..].split(',').filter_map(|b| b.parse().ok()).collect::<Vec<_>>());
+        // Biomes we don't know by name are kept as BiomeId::Unknown, but a biome that isn't a number at all is an error
+        let biomes = variable_sections
+            .get(1)
+            .map(|biome_section| {
+                biome_section
+                    .trim_start_matches("biomes_")
+                    .split(',')
+                    .filter(|b| !b.is_empty())
+                    .map(|b| b.trim().parse::<u8>().map(BiomeId::from_u8).map_err(|_| anyhow!("Invalid biome ID \"{b}\"")))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?;
         Ok(MapChanceData {
             mapChance,
             biomes
@@ -113,6 +111,107 @@ impl FromStr for MapChanceData {
     }
 }
 
+/// Biome IDs as of data version 426. Written to the object files (and the caches) as their numeric ID.
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum BiomeId {
+    Grasslands,
+    Swamp,
+    YellowPrairies,
+    Badlands,
+    Tundra,
+    Desert,
+    Jungle,
+    DeepWater,
+    FlowerFields,
+    ShallowWater,
+    // Biomes added after data version 426, kept so the object can be written back out unchanged
+    #[strum(disabled)]
+    Unknown(u8),
+}
+
+impl BiomeId {
+    pub fn from_u8(id: u8) -> Self {
+        match id {
+            0 => BiomeId::Grasslands,
+            1 => BiomeId::Swamp,
+            2 => BiomeId::YellowPrairies,
+            3 => BiomeId::Badlands,
+            4 => BiomeId::Tundra,
+            5 => BiomeId::Desert,
+            6 => BiomeId::Jungle,
+            7 => BiomeId::DeepWater,
+            8 => BiomeId::FlowerFields,
+            9 => BiomeId::ShallowWater,
+            id => BiomeId::Unknown(id),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            BiomeId::Grasslands => 0,
+            BiomeId::Swamp => 1,
+            BiomeId::YellowPrairies => 2,
+            BiomeId::Badlands => 3,
+            BiomeId::Tundra => 4,
+            BiomeId::Desert => 5,
+            BiomeId::Jungle => 6,
+            BiomeId::DeepWater => 7,
+            BiomeId::FlowerFields => 8,
+            BiomeId::ShallowWater => 9,
+            BiomeId::Unknown(id) => *id,
+        }
+    }
+}
+
+impl ToString for BiomeId {
+    fn to_string(&self) -> String {
+        match self {
+            BiomeId::Grasslands => "Grasslands".to_string(),
+            BiomeId::Swamp => "Swamp".to_string(),
+            BiomeId::YellowPrairies => "Yellow Prairies".to_string(),
+            BiomeId::Badlands => "Badlands".to_string(),
+            BiomeId::Tundra => "Tundra".to_string(),
+            BiomeId::Desert => "Desert".to_string(),
+            BiomeId::Jungle => "Jungle".to_string(),
+            BiomeId::DeepWater => "Deep Water".to_string(),
+            BiomeId::FlowerFields => "Flower Fields".to_string(),
+            BiomeId::ShallowWater => "Shallow Water".to_string(),
+            BiomeId::Unknown(id) => format!("Biome {id}"),
+        }
+    }
+}
+
+impl FromStr for BiomeId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if let Ok(id) = s.trim().parse::<u8>() {
+            return Ok(BiomeId::from_u8(id));
+        }
+        BiomeId::iter()
+            .find(|biome| biome.to_string().to_lowercase().replace(' ', "") == s.trim().to_lowercase().replace(['_', ' '], ""))
+            .ok_or_else(|| anyhow!("Unknown biome {s} (expected a biome ID or name, e.g. 0 or Grasslands)"))
+    }
+}
+
+impl Serialize for BiomeId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(self.to_u8())
+    }
+}
+
+impl<'de> Deserialize<'de> for BiomeId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        u8::deserialize(deserializer).map(BiomeId::from_u8)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PersonData {
     pub person: i8,
@@ -136,16 +235,13 @@ impl FromStr for PersonData {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let variable_sections = s.trim().split(',').collect::<Vec<_>>();
         // First section is person. Beyond that, we deal with whatever supported values are present
-        let person = variable_sections[0]
-        .split('=')
-        .collect::<Vec<_>>()
-        [1]
+        let person = section_value(variable_sections[0])?
         .parse::<i8>()?;
         let mut noSpawn = None;
         for &variable_section in variable_sections.iter().skip(1) {
             let variable_data = variable_section.split('=').collect::<Vec<_>>();
             match variable_data[0] {
-                "noSpawn" => noSpawn = Some(variable_data[1].parse::<i8>()?.to_bool()),
+                "noSpawn" => noSpawn = Some(section_value(variable_section)?.parse::<i8>()?.to_bool()),
                 _ => {
                     log::info!("PersonData::FromStr: Unexpected variable name {}", variable_data[0]);
                 }
@@ -181,17 +277,14 @@ impl FromStr for PermanentData {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let variable_sections = s.trim().split(',').collect::<Vec<_>>();
         // First section is permanent. Beyond that, we deal with whatever supported values are present
-        let permanent = variable_sections[0]
-        .split('=')
-        .collect::<Vec<_>>()
-        [1]
+        let permanent = section_value(variable_sections[0])?
         .parse::<i8>()?
         .to_bool();
         let mut minPickupAge = None;
         for &variable_section in variable_sections.iter().skip(1) {
             let variable_data = variable_section.split('=').collect::<Vec<_>>();
             match variable_data[0] {
-                "minPickupAge" => minPickupAge = Some(variable_data[1].parse()?),
+                "minPickupAge" => minPickupAge = Some(section_value(variable_section)?.parse()?),
                 _ => {
                     log::info!("PermanentData::FromStr: Unexpected variable name {}", variable_data[0]);
                 }
@@ -228,10 +321,7 @@ impl FromStr for ContainSizeData {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let variable_sections = s.trim().split(',').collect::<Vec<_>>();
         // First section is containSize, second optional section is vertSlotRotlet containSize_val = main_variable_value.parse::<f32>()?;
-        let containSize_var_str = variable_sections[0]
-        .split('=')
-        .collect::<Vec<_>>()
-        [1];
+        let containSize_var_str = section_value(variable_sections[0])?;
         let containSizeF32Result = containSize_var_str.parse::<f32>();
         let containSize = if let Ok(containSizeF32) = containSizeF32Result {
             containSizeF32
@@ -241,14 +331,14 @@ impl FromStr for ContainSizeData {
                 containSizeI32 as f32
             }
             else {
-                panic!("Invalid containSize data: {s}");
+                return Err(anyhow!("Invalid containSize data: {s}"));
             }
         };
         let mut vertSlotRot = None;
         for &variable_section in variable_sections.iter().skip(1) {
             let variable_data = variable_section.split('=').collect::<Vec<_>>();
             match variable_data[0] {
-                "vertSlotRot" => vertSlotRot = Some(variable_data[1].parse()?),
+                "vertSlotRot" => vertSlotRot = Some(section_value(variable_section)?.parse()?),
                 _ => {
                     log::info!("ContainsSizeData::FromStr: Unexpected variable name {}", variable_data[0]);
                 }
@@ -273,7 +363,7 @@ impl FromStr for ClothingData {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let clothing = s.trim().split('=').collect::<Vec<_>>()[1].parse()?;
+        let clothing = section_value(s.trim())?.parse()?;
 
         Ok(ClothingData { clothing })
     }
@@ -290,6 +380,21 @@ pub enum ClothingType {
     None,
 }
 
+impl ClothingType {
+    /// The share of the body's insulation this slot covers, as the game (and twotech) weigh it.
+    /// A piece of clothing's insulation is its rValue times this. Shoes are worn in pairs, so each covers 0.1.
+    pub fn insulation_weight(&self) -> f64 {
+        match self {
+            ClothingType::Head => 0.25,
+            ClothingType::Top => 0.35,
+            ClothingType::Bottom => 0.2,
+            ClothingType::Shoe => 0.1,
+            ClothingType::Pack => 0.1,
+            ClothingType::Shield | ClothingType::None => 0.0,
+        }
+    }
+}
+
 impl ToString for ClothingType {
     fn to_string(&self) -> String {
         match self {
@@ -324,132 +429,183 @@ impl FromStr for ClothingType {
     }
 }
 
+/// The sounds an object plays, e.g. sounds=201:0.250000,-1:0.0,305:0.500000#306:0.250000,-1:0.0
+/// There are four comma-separated groups (creation, using, eating, decay), and each group is a
+/// #-separated list of soundID:volume pairs. A sound ID of -1 means no sound.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SoundsData {
-    pub data: String,
+    pub creationSound: SoundDataVec,
+    pub usingSound: SoundDataVec,
+    pub eatingSound: SoundDataVec,
+    pub decaySound: SoundDataVec,
+    // The original line, only kept if writing the parsed sounds back out wouldn't reproduce it exactly
+    // (e.g. an unusual volume precision), so the object file still round-trips
+    pub rawLine: Option<String>,
+}
+
+/// Which of an object's four sound groups a sound is played for
+#[derive(Clone, Copy, Debug, EnumIter, PartialEq, Serialize, Deserialize)]
+pub enum SoundGroup {
+    Creation,
+    Using,
+    Eating,
+    Decay,
+}
+
+impl ToString for SoundGroup {
+    fn to_string(&self) -> String {
+        match self {
+            SoundGroup::Creation => "Creation",
+            SoundGroup::Using => "Using",
+            SoundGroup::Eating => "Eating",
+            SoundGroup::Decay => "Decay",
+        }.to_string()
+    }
+}
+
+impl FromStr for SoundGroup {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "creation" => Ok(SoundGroup::Creation),
+            "using" | "use" => Ok(SoundGroup::Using),
+            "eating" | "eat" => Ok(SoundGroup::Eating),
+            "decay" => Ok(SoundGroup::Decay),
+            _ => Err(anyhow!("Unknown sound group {s} (expected creation, using, eating or decay)"))
+        }
+    }
+}
+
+impl SoundsData {
+    pub fn group(&self, group: SoundGroup) -> &SoundDataVec {
+        match group {
+            SoundGroup::Creation => &self.creationSound,
+            SoundGroup::Using => &self.usingSound,
+            SoundGroup::Eating => &self.eatingSound,
+            SoundGroup::Decay => &self.decaySound,
+        }
+    }
+
+    /// Every actual sound (ignoring the -1 placeholders), with the group it belongs to
+    pub fn sounds(&self) -> Vec<(SoundGroup, &SoundData)> {
+        SoundGroup::iter()
+            .flat_map(|group| self.group(group).0.iter().filter(|sound| sound.is_sound()).map(move |sound| (group, sound)))
+            .collect()
+    }
+
+    pub fn uses_sound(&self, id: i32) -> bool {
+        self.sounds().iter().any(|(_, sound)| sound.id == id)
+    }
+
+    pub fn has_sound(&self, group: SoundGroup) -> bool {
+        self.group(group).0.iter().any(SoundData::is_sound)
+    }
+
+    fn canonical_string(&self) -> String {
+        format!(
+            "sounds={},{},{},{}",
+            self.creationSound.to_string(),
+            self.usingSound.to_string(),
+            self.eatingSound.to_string(),
+            self.decaySound.to_string(),
+        )
+    }
 }
 
 impl ToString for SoundsData {
     fn to_string(&self) -> String {
-        self.data.clone()
+        match &self.rawLine {
+            Some(rawLine) => rawLine.clone(),
+            None => self.canonical_string(),
+        }
     }
 }
 
 impl FromStr for SoundsData {
     type Err = anyhow::Error;
 
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        Ok(SoundsData {
-            data: s.to_string()
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = s.trim();
+        let mut groups = section_value(line)?.split(',');
+        // Missing groups are treated as silent, and rawLine keeps the line as it was
+        let mut next_group = || groups.next().map(SoundDataVec::from_str).transpose().map(Option::unwrap_or_default);
+        let mut sounds = SoundsData {
+            creationSound: next_group()?,
+            usingSound: next_group()?,
+            eatingSound: next_group()?,
+            decaySound: next_group()?,
+            rawLine: None,
+        };
+        if sounds.canonical_string() != line {
+            sounds.rawLine = Some(line.to_string());
+        }
+        Ok(sounds)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SoundData {
+    pub id: i32,
+    pub volume: f64
+}
+
+impl SoundData {
+    /// False for the -1 placeholder used when a group has no sound
+    pub fn is_sound(&self) -> bool {
+        self.id != -1
+    }
+}
+
+impl ToString for SoundData {
+    fn to_string(&self) -> String {
+        if self.id == -1 && self.volume == 0.0 {
+            format!("{}:{:.1}", self.id, self.volume)
+        } else {
+            format!("{}:{:.6}", self.id, self.volume)
+        }
+    }
+}
+
+impl FromStr for SoundData {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (id, volume) = s
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Expected soundID:volume, found \"{s}\""))?;
+        Ok(SoundData {
+            id: id.parse()?,
+            volume: volume.parse()?
         })
     }
 }
 
-// #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-// pub struct SoundsData {
-//     pub creationSound: SoundDataVec,
-//     pub usingSound: SoundDataVec,
-//     pub eatingSound: SoundDataVec,
-//     pub decaySound: SoundDataVec,
-// }
-
-// impl ToString for SoundsData {
-//     fn to_string(&self) -> String {
-//         let mut output = String::new();
-//         output.push_str("sounds=");
-//         output.push_str(&self.creationSound.to_string());
-//         output.push(',');
-//         output.push_str(&self.usingSound.to_string());
-//         output.push(',');
-//         output.push_str(&self.eatingSound.to_string());
-//         output.push(',');
-//         output.push_str(&self.decaySound.to_string());
-//         output
-//     }
-// }
-
-// impl FromStr for SoundsData {
-//     type Err = anyhow::Error;
-
-//     fn from_str(s: &str) -> Result<Self, Self::Err> {
-//         let line_sections = s.trim().split('=').collect::<Vec<_>>();
-//         let variable_sections = line_sections[1].split(',').collect::<Vec<_>>();
-//         Ok(SoundsData {
-//             creationSound: SoundDataVec::from_str(variable_sections[0])?,
-//             usingSound: SoundDataVec::from_str(variable_sections[1])?,
-//             eatingSound: SoundDataVec::from_str(variable_sections[2])?,
-//             decaySound: SoundDataVec::from_str(variable_sections[3])?,
-//         })
-//     }
-// }
-
-// #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-// pub struct SoundData {
-//     pub id: i32,
-//     pub volume: f64
-// }
-
-// impl ToString for SoundData {
-//     fn to_string(&self) -> String {
-//         if self.id == -1 && self.volume == 0.0 {
-//             format!("{}:{:.1}", self.id, self.volume)
-//         } else {
-//             format!("{}:{:.6}", self.id, self.volume)
-//         }
-//     }
-// }
-
-// //use the code from object's fromstr and put it here instead, and then use it in object's fromstr
-// impl FromStr for SoundData {
-//     type Err = anyhow::Error;
-
-//     fn from_str(s: &str) -> Result<Self, Self::Err> {
-//         let variable_sections = s.trim().split(':').collect::<Vec<_>>();
-//         Ok(SoundData {
-//             id: variable_sections[0].parse()?,
-//             volume: variable_sections[1].parse()?
-//         })
-//     }
-// }
-
-// #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-// pub struct SoundDataVec(pub Vec<SoundData>);
-
-// impl ToString for SoundDataVec {
-//     fn to_string(&self) -> String {
-//         self.0
-//         .iter()
-//         .map(|s| s.to_string())
-//         .collect::<Vec<_>>()
-//         .join("#")
-//     }
-// }
-
-// //use the code from object's fromstr and put it here instead, and then use it in object's fromstr
-// impl FromStr for SoundDataVec {
-//     type Err = anyhow::Error;
-
-//     fn from_str(s: &str) -> Result<Self, Self::Err> {
-//         let sound_sections = s.trim()
-//             .split('#')
-//             .filter_map(|sound_section| {
-//                 let sound_data_sections = sound_section.split(':').collect::<Vec<_>>();
-//                 let id = sound_data_sections[0].parse::<i32>();
-//                 let volume = sound_data_sections[1].parse::<f64>();
-//                 if id.is_err() || volume.is_err() {
-//                     log::warn!("Error parsing id ({}) or volume ({}), using a None value for this sound section", if id.is_err() { "NOT OK"} else { "OK" }, if volume.is_err() { "NOT OK"} else { "OK" });
-//                     return None;
-//                 } else {
-//                     return Some(SoundData{ id: id.unwrap(), volume: volume.unwrap() })
-//                 }
-//             })
-//             .collect::<Vec<_>>();
-
-//         Ok(SoundDataVec {
-//             0: sound_sections
-//         })
-//     }
-// }
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SoundDataVec(pub Vec<SoundData>);
+
+impl ToString for SoundDataVec {
+    fn to_string(&self) -> String {
+        self.0
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>()
+        .join("#")
+    }
+}
+
+impl FromStr for SoundDataVec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sounds = s.trim()
+            .split('#')
+            .map(SoundData::from_str)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(SoundDataVec(sounds))
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct NumSlotsData {
@@ -474,16 +630,13 @@ impl FromStr for NumSlotsData {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let variable_sections = s.trim().split('#').collect::<Vec<_>>();
         // First section is numSlots. Beyond that, we deal with whatever supported values are present
-        let numSlots = variable_sections[0]
-        .split('=')
-        .collect::<Vec<_>>()
-        [1]
+        let numSlots = section_value(variable_sections[0])?
         .parse()?;
         let mut timeStretch = None;
         for &variable_section in variable_sections.iter().skip(1) {
             let variable_data = variable_section.split('=').collect::<Vec<_>>();
             match variable_data[0] {
-                "timeStretch" => timeStretch = Some(variable_data[1].parse()?),
+                "timeStretch" => timeStretch = Some(section_value(variable_section)?.parse()?),
                 _ => {
                     log::info!("NumSlotsData::FromStr: Unexpected variable name {}", variable_data[0]);
                 }
@@ -513,12 +666,13 @@ impl FromStr for ColorData {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let color_data = s.trim().split('=')
-        .collect::<Vec<_>>()
-        [1]
+        let color_data = section_value(s.trim())?
         .split(',')
         .filter_map(|c| c.parse().ok())
         .collect::<Vec<_>>();
+        if color_data.len() < 3 {
+            return Err(anyhow!("Expected three color values, found \"{s}\""));
+        }
         Ok(ColorData {
             red: color_data[0],
             green: color_data[1],
@@ -571,35 +725,35 @@ parent={}
 }
 
 impl SpriteData {
-    fn is_sprite_data<'a>(variable_name: &str) -> bool {
-        match variable_name {
-            "pos" => true,
-            "rot" => true,
-            "hFlip" => true,
-            "color" => true,
-            "ageRange" => true,
-            "parent" => true,
-            "invisHolding" => true,
-            "invisCont" => true,
-            "spritesDrawnBehind" => true,
-            "spritesAdditiveBlend" => true,
-            "ignoredCont" => true,
-            _ => false,
-        }
+    fn is_sprite_data(variable_name: &str) -> bool {
+        matches!(
+            variable_name,
+            "pos"
+            | "rot"
+            | "hFlip"
+            | "color"
+            | "ageRange"
+            | "parent"
+            | "invisHolding"
+            | "invisCont"
+            | "spritesDrawnBehind"
+            | "spritesAdditiveBlend"
+            | "ignoredCont"
+        )
     }
 }
 
 impl FromStr for SpriteData {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
+    // Line numbers in errors are relative to the spriteID line (line 1). OneLifeDataObject::from_str shifts them to file line numbers.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let variable_sections = s.trim().split('\n').collect::<Vec<_>>();
+        let line_error = |index: usize, field: &str, e: anyhow::Error| {
+            ParseError::new(format!("{e:#}")).at_line(index + 1, variable_sections[index]).with_field(field)
+        };
         // First section is spriteID. Beyond that, we deal with whatever supported values are present
-        let spriteID = variable_sections[0]
-        .split('=')
-        .collect::<Vec<_>>()
-        [1]
-        .parse()?;
+        let spriteID = parse_section_value(variable_sections[0]).map_err(|e| line_error(0, "spriteID", e))?;
         let mut pos = None;
         let mut rot = None;
         let mut hFlip = None;
@@ -611,44 +765,35 @@ impl FromStr for SpriteData {
         let mut spritesDrawnBehind = None;
         let mut spritesAdditiveBlend = None;
         let mut ignoredCont = None;
-        for &variable_section in variable_sections.iter().skip(1) {
-            let variable_data = variable_section.split('=').map(|v| v.trim()).collect::<Vec<_>>();
-            match variable_data[0] {
-                "pos" => pos = Some(variable_data[1].parse::<DoublePair>().expect(&format!("Error parsing pos value: {}", variable_data[1]))),
-                "rot" => rot = Some(variable_data[1].parse::<f64>().expect(&format!("Error parsing rot value: {}", variable_data[1]))),
-                "hFlip" => hFlip = Some(variable_data[1].parse::<i8>().expect("Error parsing hFlip value").to_bool()),
-                "color" => color = Some(variable_section.parse::<ColorData>().expect("Error parsing color value")),
-                "ageRange" => ageRange = Some(variable_data[1].parse::<DoublePair>().expect("Error parsing ageRange value")),
-                "parent" => parent = Some(variable_data[1].parse::<i32>().expect("Error parsing parent value")),
-                "invisHolding" => invisHolding = Some(variable_section.parse::<InvisHoldingData>().expect("Error parsing invisHolding value")),
-                "invisCont" => invisCont = Some(variable_data[1].parse::<i8>().expect("Error parsing invisCont value").to_bool()),
-                "spritesDrawnBehind" => spritesDrawnBehind = Some(variable_data[1]
-                    .split(",")
-                    .filter_map(|v| v.parse::<i8>().ok())
-                    .collect::<Vec<_>>()
-                ),
-                "spritesAdditiveBlend" => spritesAdditiveBlend = Some(variable_data[1]
-                    .split(",")
-                    .filter_map(|v| v.parse::<i8>().ok())
-                    .collect::<Vec<_>>()
-                ),
-                "ignoredCont" => ignoredCont = Some(variable_data[1]
-                    .split(",")
-                    .filter_map(|v| v.parse::<i8>().ok())
-                    .collect::<Vec<_>>()
-                ),
+        for (index, &variable_section) in variable_sections.iter().enumerate().skip(1) {
+            let variable_name = variable_section.split('=').next().unwrap_or_default().trim();
+            let result: Result<()> = match variable_name {
+                "pos" => parse_section_value(variable_section).map(|v| pos = Some(v)),
+                "rot" => parse_section_value(variable_section).map(|v| rot = Some(v)),
+                "hFlip" => parse_section_value::<i8>(variable_section).map(|v| hFlip = Some(v.to_bool())),
+                "color" => variable_section.parse::<ColorData>().map(|v| color = Some(v)),
+                "ageRange" => parse_section_value(variable_section).map(|v| ageRange = Some(v)),
+                "parent" => parse_section_value(variable_section).map(|v| parent = Some(v)),
+                "invisHolding" => variable_section.parse::<InvisHoldingData>().map(|v| invisHolding = Some(v)),
+                "invisCont" => parse_section_value::<i8>(variable_section).map(|v| invisCont = Some(v.to_bool())),
+                "spritesDrawnBehind" => section_value(variable_section).map(|v| spritesDrawnBehind = Some(parse_i8_list(v))),
+                "spritesAdditiveBlend" => section_value(variable_section).map(|v| spritesAdditiveBlend = Some(parse_i8_list(v))),
+                "ignoredCont" => section_value(variable_section).map(|v| ignoredCont = Some(parse_i8_list(v))),
                 _ => {
-                    log::info!("SpriteData::FromStr: Unexpected variable name {}", variable_data[0]);
+                    log::info!("SpriteData::FromStr: Unexpected variable name {}", variable_name);
+                    Ok(())
                 }
-            }
-        }
-        let pos = pos.ok_or_else(|| anyhow!("Missing required value for \"pos\""))?;
-        let rot = rot.ok_or_else(|| anyhow!("Missing required value for \"rot\""))?;
-        let hFlip = hFlip.ok_or_else(|| anyhow!("Missing required value for \"hFlip\""))?;
-        let color = color.ok_or_else(|| anyhow!("Missing required value for \"color\""))?;
-        let ageRange = ageRange.ok_or_else(|| anyhow!("Missing required value for \"ageRange\""))?;
-        let parent = parent.ok_or_else(|| anyhow!("Missing required value for \"parent\""))?;
-        let invisHolding = invisHolding.ok_or_else(|| anyhow!("Missing required value for \"invisHolding\""))?;
+            };
+            result.map_err(|e| line_error(index, variable_name, e))?;
+        }
+        let missing = |field: &str| ParseError::new("Missing required value").at_line(1, variable_sections[0]).with_field(field);
+        let pos = pos.ok_or_else(|| missing("pos"))?;
+        let rot = rot.ok_or_else(|| missing("rot"))?;
+        let hFlip = hFlip.ok_or_else(|| missing("hFlip"))?;
+        let color = color.ok_or_else(|| missing("color"))?;
+        let ageRange = ageRange.ok_or_else(|| missing("ageRange"))?;
+        let parent = parent.ok_or_else(|| missing("parent"))?;
+        let invisHolding = invisHolding.ok_or_else(|| missing("invisHolding"))?;
         Ok(SpriteData {
             spriteID,
             pos,
@@ -666,6 +811,12 @@ impl FromStr for SpriteData {
     }
 }
 
+fn parse_i8_list(s: &str) -> Vec<i8> {
+    s.split(",")
+        .filter_map(|v| v.parse::<i8>().ok())
+        .collect::<Vec<_>>()
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DoublePair(pub f64, pub f64);
 
@@ -680,6 +831,9 @@ impl FromStr for DoublePair {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts = s.trim().split(',').filter_map(|f| f.parse().ok()).collect::<Vec<_>>();
+        if parts.len() < 2 {
+            return Err(anyhow!("Expected two numbers, found \"{s}\""));
+        }
         Ok(DoublePair(parts[0], parts[1]))
     }
 }
@@ -698,6 +852,9 @@ impl FromStr for I32Pair {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts = s.trim().split(',').filter_map(|f| f.parse().ok()).collect::<Vec<_>>();
+        if parts.len() < 2 {
+            return Err(anyhow!("Expected two numbers, found \"{s}\""));
+        }
         Ok(I32Pair(parts[0], parts[1]))
     }
 }
@@ -721,10 +878,7 @@ impl FromStr for InvisHoldingData {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let variable_sections = s.trim().split(',').collect::<Vec<_>>();
         // First section is invisHolding. Beyond that, we deal with whatever supported values are present
-        let invisHolding = variable_sections[0]
-        .split('=')
-        .collect::<Vec<_>>()
-        [1]
+        let invisHolding = section_value(variable_sections[0])?
         .parse::<i8>()?
         .to_bool();
         let mut invisWorn = None;
@@ -732,8 +886,8 @@ impl FromStr for InvisHoldingData {
         for &variable_section in variable_sections.iter().skip(1) {
             let variable_data = variable_section.split('=').collect::<Vec<_>>();
             match variable_data[0] {
-                "invisWorn" => invisWorn = Some(variable_data[1].parse::<i32>()?),
-                "behindSlots" => behindSlots = Some(variable_data[1].parse::<i8>()?.to_bool()),
+                "invisWorn" => invisWorn = Some(section_value(variable_section)?.parse::<i32>()?),
+                "behindSlots" => behindSlots = Some(section_value(variable_section)?.parse::<i8>()?.to_bool()),
                 _ => {
                     log::info!("InvisHoldingData::FromStr: Unexpected variable name {}", variable_data[0]);
                 }
@@ -772,10 +926,7 @@ impl FromStr for NumUsesData {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let variable_sections = s.trim().split(',').collect::<Vec<_>>();
         // First section is numUses. Beyond that, we deal with whatever supported values are present
-        let numUses = variable_sections[0]
-        .split('=')
-        .collect::<Vec<_>>()
-        [1]
+        let numUses = section_value(variable_sections[0])?
         .parse()?;
         let useChance = if variable_sections.len() > 1 {
             variable_sections[1].parse().ok()
@@ -815,18 +966,18 @@ impl FromStr for SlotPosData {
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         let variable_sections = s.trim().split(",").collect::<Vec<_>>();
-        let slotPos = [variable_sections[0], variable_sections[1]].join(",")
-        .split('=')
-        .collect::<Vec<_>>()
-        [1]
+        if variable_sections.len() < 2 {
+            return Err(anyhow!("Expected an x,y slot position, found \"{s}\""));
+        }
+        let slotPos = section_value(&[variable_sections[0], variable_sections[1]].join(","))?
         .parse()?;
         let mut vert = None;
         let mut parent = None;
         for &variable_section in variable_sections.iter().skip(2) {
             let variable_data = variable_section.split('=').collect::<Vec<_>>();
             match variable_data[0] {
-                "vert" => vert = Some(variable_data[1].parse()?),
-                "parent" => parent = Some(variable_data[1].parse()?),
+                "vert" => vert = Some(section_value(variable_section)?.parse()?),
+                "parent" => parent = Some(section_value(variable_section)?.parse()?),
                 _ => {
                     log::info!("SlotPosData::FromStr: Unexpected variable name {}", variable_data[0]);
                 }
@@ -881,6 +1032,20 @@ fn GetNone<T>() -> Option<T> {
     None
 }
 
+/// A line we don't understand, kept along with the known line it followed so it can be written back out in the same place
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UnknownLine {
+    // The variable name of the known line before this one, and which occurrence of that name it was (from 1, since
+    // names like spriteID repeat). None if it came right after the object's name.
+    pub after: Option<(String, usize)>,
+    pub line: String,
+}
+
+// The variable name a known line starts with, e.g. "permanent" for "permanent=0,minPickupAge=3"
+fn line_variable_name(line: &str) -> &str {
+    line.trim().split('=').next().unwrap_or_default()
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct OneLifeDataObject {
     pub id: i32,
@@ -933,6 +1098,7 @@ pub struct OneLifeDataObject {
     pub useVanishIndex: Option<Vec<i32>>,
     pub useAppearIndex: Option<Vec<i32>>,
     pub pixHeight: Option<i32>,
+    pub unknownLines: Option<Vec<UnknownLine>>,
 }
 
 pub trait ToI8 {
@@ -955,7 +1121,35 @@ pub trait ToBool {
 
 impl ToBool for i8 {
     fn to_bool(self) -> bool {
-        return self != 0;
+        self != 0
+    }
+}
+
+/// The value of a "name=value" section, or an error if there's no '='
+fn section_value(section: &str) -> Result<&str> {
+    section
+        .split('=')
+        .nth(1)
+        .ok_or_else(|| anyhow!("Expected name=value, found \"{section}\""))
+}
+
+/// The parsed value of a "name=value" section
+fn parse_section_value<T>(section: &str) -> Result<T>
+where
+    T: FromStr,
+    anyhow::Error: From<T::Err>,
+{
+    Ok(section_value(section)?.trim().parse::<T>()?)
+}
+
+impl OneLifeDataObject {
+    /// Insulation provided when worn: rValue weighted by the body part the clothing covers. None if it isn't clothing.
+    pub fn insulation(&self) -> Option<f64> {
+        let clothing = &self.clothing.as_ref()?.clothing;
+        if clothing.insulation_weight() == 0.0 {
+            return None;
+        }
+        Some(self.rValue.unwrap_or(0.0) as f64 * clothing.insulation_weight())
     }
 }
 
@@ -1152,6 +1346,33 @@ impl ToString for OneLifeDataObject {
         if let Some(pixHeight) = self.pixHeight {
             output.push(format!("pixHeight={}", pixHeight));
         }
+        if let Some(unknownLines) = &self.unknownLines {
+            // Some entries (like sprites) span multiple lines, so split everything into single lines before re-inserting
+            let known_lines = output.join("\n");
+            let mut known_lines = known_lines.split('\n');
+            let mut lines = known_lines.by_ref().take(2).map(|l| l.to_string()).collect::<Vec<_>>();
+            let mut written = vec![false; unknownLines.len()];
+            let mut insert_unknown_lines = |lines: &mut Vec<String>, anchor: Option<(&str, usize)>| {
+                for (unknownLine, written) in unknownLines.iter().zip(written.iter_mut()) {
+                    if unknownLine.after.as_ref().map(|(name, count)| (name.as_str(), *count)) == anchor {
+                        lines.push(unknownLine.line.clone());
+                        *written = true;
+                    }
+                }
+            };
+            insert_unknown_lines(&mut lines, None);
+            let mut occurrences = HashMap::new();
+            for line in known_lines {
+                let name = line_variable_name(line);
+                let occurrence = occurrences.entry(name).or_insert(0);
+                *occurrence += 1;
+                lines.push(line.to_string());
+                insert_unknown_lines(&mut lines, Some((name, *occurrence)));
+            }
+            // Lines whose known line didn't come back out go at the end, rather than getting lost
+            lines.extend(unknownLines.iter().zip(&written).filter(|(_, written)| !**written).map(|(unknownLine, _)| unknownLine.line.clone()));
+            return lines.join("\n");
+        }
         output.join("\n")
     }
 }
@@ -1170,16 +1391,18 @@ where
 }
 
 impl FromStr for OneLifeDataObject {
-    type Err = anyhow::Error;
-
-    
-// Custom deserializer for moveDistance
-    
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Leading blank lines are trimmed off, but still count towards the line numbers we report
+        let line_offset = s[..s.len() - s.trim_start().len()].matches('\n').count();
         let lines: Vec<&str> = s.trim().split('\n').collect();
-        let id = lines[0].split('=').nth(1).unwrap().trim().parse::<i32>()?;
-        let name = lines[1].to_string();
+        let id = parse_section_value::<i32>(lines[0])
+            .map_err(|e| ParseError::new(format!("{e:#}")).at_line(line_offset + 1, lines[0]).with_field("id"))?;
+        let name = lines
+            .get(1)
+            .ok_or_else(|| ParseError::new("Missing object name line").with_object_id(id))?
+            .to_string();
         let mut containable = None;
         let mut containSize = None;
         let mut mapChance = None;
@@ -1230,99 +1453,302 @@ impl FromStr for OneLifeDataObject {
 
         let mut lines_iter = lines
             .iter()
+            .enumerate()
             .peekable();
         lines_iter.next();
         lines_iter.next();
 
         let mut sprite_vec = Vec::new();
         let mut slotPos_vec = Vec::new();
+        let mut unknownLine_vec = Vec::new();
+        // The last known line so far, by variable name and occurrence, which any unknown lines get anchored to
+        let mut last_known_line: Option<(String, usize)> = None;
+        let mut known_line_counts: HashMap<String, usize> = HashMap::new();
+        let mut note_known_line = |line: &str| {
+            let name = line_variable_name(line).to_string();
+            let count = known_line_counts.entry(name.clone()).or_insert(0);
+            *count += 1;
+            Some((name, *count))
+        };
 
-        while let Some(&line) = lines_iter.next() {
-            let line = line.trim();
-            if line.is_empty() {continue;}
-            let line_sections = line.split('=').collect::<Vec<_>>();
-            let main_variable_name = line_sections[0];
-            let main_variable_value = line_sections[1];
-
-            match main_variable_name {
-                "containable" => containable = Some(main_variable_value != "0"),
-                "containSize" => containSize = Some(line.parse()?),
-                "mapChance" => mapChance = Some(line.parse()?),
-                "permanent" => permanent = Some(line.parse()?),
-                "noFlip" => noFlip = Some(main_variable_value != "0"),
-                "sideAccess" => sideAccess = Some(main_variable_value != "0"),
-                "heldInHand" => heldInHand = Some(main_variable_value.parse()?),
-                "ridingAnimationIndex" => ridingAnimationIndex = Some(main_variable_value.parse()?),
-                "blocksWalking" => blocksWalking = Some(line.parse()?),
-                "heatValue" => heatValue = Some(main_variable_value.parse()?),
-                "rValue" => rValue = Some(main_variable_value.parse()?),
-                "person" => person = Some(line.parse()?),
-                "male" => male = Some(main_variable_value != "0"),
-                "deathMarker" => deathMarker = Some(main_variable_value != "0"),
-                "homeMarker" => homeMarker = Some(main_variable_value != "0"),
-                "floor" => floor = Some(main_variable_value != "0"),
-                "partialFloor" => partialFloor = Some(main_variable_value.parse()?),
-                "floorHugging" => floorHugging = Some(main_variable_value != "0"),
-                "frontWall" => frontWall = Some(main_variable_value.parse()?),
-                "wallLayer" => wallLayer = Some(main_variable_value != "0"),
-                "foodValue" => foodValue = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()),
-                "speedMult" => speedMult = Some(main_variable_value.parse()?),
-                "containOffset" => containOffset = Some(main_variable_value.parse()?),
-                "heldOffset" => heldOffset = Some(main_variable_value.parse()?),
-                "clothing" => clothing = Some(line.parse()?),
-                "clothingOffset" => clothingOffset = Some(main_variable_value.parse()?),
-                "deadlyDistance" => deadlyDistance = Some(main_variable_value.parse()?),
-                "useDistance" => useDistance = Some(main_variable_value.parse()?),
-                "sounds" => sounds = Some(line.parse()?),
-                "creationSoundInitialOnly" => creationSoundInitialOnly = Some(main_variable_value != "0"),
-                "creationSoundForce" => creationSoundForce = Some(main_variable_value != "0"),
-                "numSlots" => numSlots = Some(line.parse()?),
-                "slotSize" => slotSize = Some(main_variable_value.parse()?),
-                "slotStyle" => slotStyle = Some(main_variable_value.parse()?),
-                "slotsLocked" => slotsLocked = Some(main_variable_value != "0"),
-                "slotsNoSwap" => slotsNoSwap = Some(main_variable_value != "0"),
-                "slotPos" => slotPos_vec.push(line.parse()?),
-                "numSprites" => numSprites = Some(main_variable_value.parse()?),
-                "spriteID" => {
-                    // We will assume numSprites has come before any sprites
-                    // But we will make sure numSprites is something and non-zero later
-                    // We must determine what to pass into SpriteData::from_str()
-                    // We have to know where to end the multi-line string we provide.
-                    // If we are not on the last sprite, there will be a future "spriteID" line
-                    // If we are on the last sprite, we must look for a non-sprite variable.
-                    // So, if we see either of those things, we have hit the end of our current sprite
-                    let variable_sections = line.split(',').collect::<Vec<_>>();
-                    let mut lines_for_sprite = vec![variable_sections[0]];
-                    while let Some(&sprite_line) = lines_iter.peek() {
-                        let sprite_variable = sprite_line.split('=').collect::<Vec<_>>()[0];
-                        if sprite_variable == "spriteID" {
-                            break;
-                        }
-                        if !SpriteData::is_sprite_data(sprite_variable) {
-                            break;
-                        }
-                        // If we passed the gates, we have a good sprite line still.
-                        // Add it to lines_for_sprite and continue.
-                        lines_for_sprite.push(*lines_iter.next().unwrap());
+        while let Some((line_index, &raw_line)) = lines_iter.next() {
+            let line = raw_line.trim();
+            let Some((main_variable_name, main_variable_value)) = line.split_once('=') else {
+                // Blank lines and lines without a variable name are kept as-is
+                unknownLine_vec.push(UnknownLine { after: last_known_line.clone(), line: raw_line.to_string() });
+                continue;
+            };
+            let main_variable_value = main_variable_value.split('=').next().unwrap_or_default();
+
+            if main_variable_name == "spriteID" {
+                // We will assume numSprites has come before any sprites
+                // But we will make sure numSprites is something and non-zero later
+                // We must determine what to pass into SpriteData::from_str()
+                // We have to know where to end the multi-line string we provide.
+                // If we are not on the last sprite, there will be a future "spriteID" line
+                // If we are on the last sprite, we must look for a non-sprite variable.
+                // So, if we see either of those things, we have hit the end of our current sprite
+                let variable_sections = line.split(',').collect::<Vec<_>>();
+                let mut lines_for_sprite = vec![variable_sections[0]];
+                while let Some(&(_, sprite_line)) = lines_iter.peek() {
+                    let sprite_variable = sprite_line.split('=').collect::<Vec<_>>()[0];
+                    if sprite_variable == "spriteID" {
+                        break;
+                    }
+                    if !SpriteData::is_sprite_data(sprite_variable) {
+                        break;
                     }
-                    sprite_vec.push(SpriteData::from_str(&lines_for_sprite.join("\n"))?);
-                },
-                "headIndex" => headIndex = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()),
-                "bodyIndex" => bodyIndex = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()),
-                "backFootIndex" => backFootIndex = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()),
-                "frontFootIndex" => frontFootIndex = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()),
-                "numUses" => numUses = Some(line.parse()?),
-                "useVanishIndex" => useVanishIndex = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()),
-                "useAppearIndex" => useAppearIndex = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()),
-                "pixHeight" => pixHeight = Some(main_variable_value.parse()?),
+                    // If we passed the gates, we have a good sprite line still.
+                    // Add it to lines_for_sprite and continue.
+                    lines_for_sprite.push(*lines_iter.next().unwrap().1);
+                }
+                let sprite = SpriteData::from_str(&lines_for_sprite.join("\n"))
+                    .map_err(|e| e.offset_lines(line_offset + line_index).with_object_id(id))?;
+                sprite_vec.push(sprite);
+                for sprite_line in lines_for_sprite {
+                    last_known_line = note_known_line(sprite_line);
+                }
+                continue;
+            }
+
+            let result: Result<()> = match main_variable_name {
+                "containable" => { containable = Some(main_variable_value != "0"); Ok(()) },
+                "containSize" => line.parse().map(|v| containSize = Some(v)),
+                "mapChance" => line.parse().map(|v| mapChance = Some(v)),
+                "permanent" => line.parse().map(|v| permanent = Some(v)),
+                "noFlip" => { noFlip = Some(main_variable_value != "0"); Ok(()) },
+                "sideAccess" => { sideAccess = Some(main_variable_value != "0"); Ok(()) },
+                "heldInHand" => main_variable_value.parse().map(|v| heldInHand = Some(v)).map_err(anyhow::Error::from),
+                "ridingAnimationIndex" => main_variable_value.parse().map(|v| ridingAnimationIndex = Some(v)).map_err(anyhow::Error::from),
+                "blocksWalking" => line.parse().map(|v| blocksWalking = Some(v)),
+                "heatValue" => main_variable_value.parse().map(|v| heatValue = Some(v)).map_err(anyhow::Error::from),
+                "rValue" => main_variable_value.parse().map(|v| rValue = Some(v)).map_err(anyhow::Error::from),
+                "person" => line.parse().map(|v| person = Some(v)),
+                "male" => { male = Some(main_variable_value != "0"); Ok(()) },
+                "deathMarker" => { deathMarker = Some(main_variable_value != "0"); Ok(()) },
+                "homeMarker" => { homeMarker = Some(main_variable_value != "0"); Ok(()) },
+                "floor" => { floor = Some(main_variable_value != "0"); Ok(()) },
+                "partialFloor" => main_variable_value.parse().map(|v| partialFloor = Some(v)).map_err(anyhow::Error::from),
+                "floorHugging" => { floorHugging = Some(main_variable_value != "0"); Ok(()) },
+                "frontWall" => main_variable_value.parse().map(|v| frontWall = Some(v)).map_err(anyhow::Error::from),
+                "wallLayer" => { wallLayer = Some(main_variable_value != "0"); Ok(()) },
+                "foodValue" => { foodValue = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()); Ok(()) },
+                "speedMult" => main_variable_value.parse().map(|v| speedMult = Some(v)).map_err(anyhow::Error::from),
+                "containOffset" => main_variable_value.parse().map(|v| containOffset = Some(v)),
+                "heldOffset" => main_variable_value.parse().map(|v| heldOffset = Some(v)),
+                "clothing" => line.parse().map(|v| clothing = Some(v)),
+                "clothingOffset" => main_variable_value.parse().map(|v| clothingOffset = Some(v)),
+                "deadlyDistance" => main_variable_value.parse().map(|v| deadlyDistance = Some(v)).map_err(anyhow::Error::from),
+                "useDistance" => main_variable_value.parse().map(|v| useDistance = Some(v)).map_err(anyhow::Error::from),
+                "sounds" => line.parse().map(|v| sounds = Some(v)),
+                "creationSoundInitialOnly" => { creationSoundInitialOnly = Some(main_variable_value != "0"); Ok(()) },
+                "creationSoundForce" => { creationSoundForce = Some(main_variable_value != "0"); Ok(()) },
+                "numSlots" => line.parse().map(|v| numSlots = Some(v)),
+                "slotSize" => main_variable_value.parse().map(|v| slotSize = Some(v)).map_err(anyhow::Error::from),
+                "slotStyle" => main_variable_value.parse().map(|v| slotStyle = Some(v)),
+                "slotsLocked" => { slotsLocked = Some(main_variable_value != "0"); Ok(()) },
+                "slotsNoSwap" => { slotsNoSwap = Some(main_variable_value != "0"); Ok(()) },
+                "slotPos" => line.parse().map(|v| slotPos_vec.push(v)),
+                "numSprites" => main_variable_value.parse().map(|v| numSprites = Some(v)).map_err(anyhow::Error::from),
+                "headIndex" => { headIndex = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()); Ok(()) },
+                "bodyIndex" => { bodyIndex = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()); Ok(()) },
+                "backFootIndex" => { backFootIndex = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()); Ok(()) },
+                "frontFootIndex" => { frontFootIndex = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()); Ok(()) },
+                "numUses" => line.parse().map(|v| numUses = Some(v)),
+                "useVanishIndex" => { useVanishIndex = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()); Ok(()) },
+                "useAppearIndex" => { useAppearIndex = Some(main_variable_value.split(",").filter_map(|v| v.parse().ok()).collect::<Vec<_>>()); Ok(()) },
+                "pixHeight" => main_variable_value.parse().map(|v| pixHeight = Some(v)).map_err(anyhow::Error::from),
                 _ => {
                     log::warn!("Unknown variable name {}", main_variable_name);
+                    unknownLine_vec.push(UnknownLine { after: last_known_line.clone(), line: raw_line.to_string() });
+                    continue;
                 }
-            }
+            };
+            result.map_err(|e| {
+                ParseError::new(format!("{e:#}"))
+                    .at_line(line_offset + line_index + 1, line)
+                    .with_field(main_variable_name)
+                    .with_object_id(id)
+            })?;
+            last_known_line = note_known_line(line);
         }
         if !sprite_vec.is_empty() { sprites = Some(sprite_vec) };
         if !slotPos_vec.is_empty() { slotPosData = Some(slotPos_vec) };
+        let unknownLines = if unknownLine_vec.is_empty() { None } else { Some(unknownLine_vec) };
+
+        Ok(OneLifeDataObject { id, name, containable, containSize, mapChance, permanent, noFlip, sideAccess, heldInHand, ridingAnimationIndex, blocksWalking, heatValue, rValue, person, male, deathMarker, homeMarker, floor, partialFloor, floorHugging, frontWall, wallLayer, foodValue, speedMult, containOffset, heldOffset, clothing, clothingOffset, deadlyDistance, useDistance, sounds, creationSoundInitialOnly, creationSoundForce, numSlots, slotSize, slotStyle, slotsLocked, slotsNoSwap, slotPosData, numSprites, sprites, headIndex, bodyIndex, backFootIndex, frontFootIndex, numUses, useVanishIndex, useAppearIndex, pixHeight, unknownLines })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // objects/9.txt from a trimmed down OneLifeData7
+    const WOLF: &str = "\
+id=9
+Wolf
+containable=1
+containSize=1.000000,vertSlotRot=0.000000
+permanent=0,minPickupAge=3
+noFlip=0
+sideAccess=0
+heldInHand=1
+blocksWalking=0,leftBlockingRadius=0,rightBlockingRadius=0,drawBehindPlayer=0
+mapChance=0.010000#biomes_4
+heatValue=0
+rValue=0.000000
+person=0,noSpawn=0
+male=0
+deathMarker=0
+homeMarker=0
+floor=0
+floorHugging=0
+foodValue=0,0
+speedMult=1.500000
+heldOffset=0.000000,0.000000
+clothing=n
+clothingOffset=0.000000,0.000000
+deadlyDistance=2
+useDistance=1
+sounds=-1:0.0,-1:0.0,-1:0.0,-1:0.0
+creationSoundInitialOnly=0
+creationSoundForce=0
+numSlots=0#timeStretch=1.000000
+slotSize=1.000000
+slotStyle=0
+slotsLocked=0
+numSprites=1
+spriteID=100
+pos=0.000000,0.000000
+rot=0.000000
+hFlip=0
+color=1.000000,1.000000,1.000000
+ageRange=-1.000000,-1.000000
+parent=-1
+invisHolding=0,invisWorn=0,behindSlots=0
+invisCont=0
+spritesDrawnBehind=
+spritesAdditiveBlend=
+headIndex=-1
+bodyIndex=-1
+backFootIndex=-1
+frontFootIndex=-1
+numUses=1,0.000000
+useVanishIndex=-1
+useAppearIndex=-1
+pixHeight=0";
+
+    // The wolf with extra lines added after the first line starting with `after`
+    fn wolf_with_lines_after(after: &str, extra_lines: &[&str]) -> String {
+        let mut lines = WOLF.lines().collect::<Vec<_>>();
+        let index = lines.iter().position(|line| line.starts_with(after)).unwrap();
+        lines.splice(index + 1..index + 1, extra_lines.iter().copied());
+        lines.join("\n")
+    }
+
+    fn assert_roundtrips(text: &str) -> OneLifeDataObject {
+        let object = text.parse::<OneLifeDataObject>().unwrap();
+        assert_eq!(object.to_string(), text);
+        object
+    }
+
+    #[test]
+    fn known_lines_roundtrip() {
+        let object = assert_roundtrips(WOLF);
+        assert_eq!(object.unknownLines, None);
+    }
+
+    #[test]
+    fn unknown_line_in_the_middle_roundtrips() {
+        let object = assert_roundtrips(&wolf_with_lines_after("heldInHand=", &["futureField=7"]));
+        let unknown_lines = object.unknownLines.unwrap();
+        assert_eq!(unknown_lines.len(), 1);
+        assert_eq!(unknown_lines[0].after, Some(("heldInHand".to_string(), 1)));
+    }
+
+    #[test]
+    fn unknown_line_at_the_end_roundtrips() {
+        assert_roundtrips(&wolf_with_lines_after("pixHeight=", &["futureField=7"]));
+    }
+
+    #[test]
+    fn consecutive_unknown_lines_roundtrip() {
+        let object = assert_roundtrips(&wolf_with_lines_after("rValue=", &["futureField=7", "otherFutureField=1,2"]));
+        assert_eq!(object.unknownLines.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn unknown_line_before_the_known_lines_roundtrips() {
+        assert_roundtrips(&wolf_with_lines_after("Wolf", &["futureField=7"]));
+    }
+
+    fn wolf_with_line_replaced(prefix: &str, line: &str) -> String {
+        WOLF.lines().map(|l| if l.starts_with(prefix) { line } else { l }).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn parse_error_points_at_the_bad_line() {
+        let error = wolf_with_line_replaced("heldInHand=", "heldInHand=abc").parse::<OneLifeDataObject>().unwrap_err();
+        assert_eq!(error.object_id, Some(9));
+        assert_eq!(error.line_number, Some(8));
+        assert_eq!(error.field.as_deref(), Some("heldInHand"));
+        assert_eq!(error.text, "heldInHand=abc");
+    }
+
+    #[test]
+    fn parse_error_line_numbers_count_leading_blank_lines() {
+        let text = format!("\n\n{}", wolf_with_line_replaced("deadlyDistance=", "deadlyDistance=far"));
+        let error = text.parse::<OneLifeDataObject>().unwrap_err();
+        assert_eq!(error.line_number, Some(text.lines().position(|line| line == "deadlyDistance=far").unwrap() + 1));
+        assert_eq!(error.field.as_deref(), Some("deadlyDistance"));
+    }
+
+    #[test]
+    fn parse_error_in_a_sprite_block_points_at_the_bad_line() {
+        let text = wolf_with_line_replaced("rot=", "rot=sideways");
+        let error = text.parse::<OneLifeDataObject>().unwrap_err();
+        assert_eq!(error.line_number, Some(text.lines().position(|line| line == "rot=sideways").unwrap() + 1));
+        assert_eq!(error.text, "rot=sideways");
+    }
+
+    #[test]
+    fn sounds_roundtrip() {
+        let line = "sounds=12:0.250000#13:0.500000,-1:0.0,-1:0.0,14:1.000000";
+        let sounds = line.parse::<SoundsData>().unwrap();
+        assert_eq!(sounds.rawLine, None);
+        assert_eq!(sounds.creationSound.0.iter().map(|sound| sound.id).collect::<Vec<_>>(), [12, 13]);
+        assert_eq!(sounds.decaySound.0[0].volume, 1.0);
+        assert!(!sounds.has_sound(SoundGroup::Eating));
+        assert_eq!(sounds.to_string(), line);
+        assert_roundtrips(&wolf_with_line_replaced("sounds=", line));
+    }
+
+    #[test]
+    fn unusual_sounds_line_comes_back_out_unchanged() {
+        // Two of the four groups missing, and a volume written with fewer decimal places than usual
+        let line = "sounds=12:0.25,-1:0.0";
+        let sounds = line.parse::<SoundsData>().unwrap();
+        assert_eq!(sounds.rawLine.as_deref(), Some(line));
+        assert_eq!((sounds.creationSound.0[0].id, sounds.creationSound.0[0].volume), (12, 0.25));
+        assert!(sounds.eatingSound.0.is_empty() && sounds.decaySound.0.is_empty());
+        assert_eq!(sounds.to_string(), line);
+        assert_roundtrips(&wolf_with_line_replaced("sounds=", line));
+    }
+
+    #[test]
+    fn map_chance_biomes() {
+        let map_chance = "mapChance=0.500000#biomes_0,4,12".parse::<MapChanceData>().unwrap();
+        assert_eq!(map_chance.biomes, Some(vec![BiomeId::Grasslands, BiomeId::Tundra, BiomeId::Unknown(12)]));
+        assert_eq!(map_chance.to_string(), "mapChance=0.500000#biomes_0,4,12");
+        assert_eq!("mapChance=0.000000#biomes_".parse::<MapChanceData>().unwrap().to_string(), "mapChance=0.000000#biomes_");
+    }
 
-        Ok(OneLifeDataObject { id, name, containable, containSize, mapChance, permanent, noFlip, sideAccess, heldInHand, ridingAnimationIndex, blocksWalking, heatValue, rValue, person, male, deathMarker, homeMarker, floor, partialFloor, floorHugging, frontWall, wallLayer, foodValue, speedMult, containOffset, heldOffset, clothing, clothingOffset, deadlyDistance, useDistance, sounds, creationSoundInitialOnly, creationSoundForce, numSlots, slotSize, slotStyle, slotsLocked, slotsNoSwap, slotPosData, numSprites, sprites, headIndex, bodyIndex, backFootIndex, frontFootIndex, numUses, useVanishIndex, useAppearIndex, pixHeight })
+    #[test]
+    fn bad_biome_is_a_parse_error() {
+        assert!("mapChance=0.500000#biomes_0,x".parse::<MapChanceData>().is_err());
+        let error = wolf_with_line_replaced("mapChance=", "mapChance=0.010000#biomes_4,tundra").parse::<OneLifeDataObject>().unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("mapChance"));
+        assert_eq!(error.text, "mapChance=0.010000#biomes_4,tundra");
     }
 }
@@ -0,0 +1,293 @@
+#![allow(non_snake_case)]
+#![allow(clippy::to_string_trait_impl)]
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::one_life_data_object::ToI8;
+use crate::twotech_object::MoveType;
+
+/// A single OneLifeData7 transition, parsed from transitions/actor_target[_LA|_LT|_L].txt
+///
+/// The file name holds the actor and target IDs (and whether this is the last-use variant),
+/// while the file contents are one line of space-separated values, e.g. "0 34 0 0.000000 0.000000 0 0 0 1 0 0"
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+    // Special actor IDs: 0 => bare hand, -1 => time passing (decay), -2 => default transition for the target
+    pub actorID: i32,
+    // Special target IDs: -1 => actor used on the bare ground
+    pub targetID: i32,
+    pub lastUseActor: bool,
+    pub lastUseTarget: bool,
+    pub newActorID: i32,
+    pub newTargetID: i32,
+    // Negative values are in hours instead of seconds
+    pub autoDecaySeconds: i32,
+    pub actorMinUseFraction: f32,
+    pub targetMinUseFraction: f32,
+    pub reverseUseActor: bool,
+    pub reverseUseTarget: bool,
+    #[serde(rename = "move")]
+    pub movement: MoveType,
+    pub desiredMoveDist: i32,
+    pub noUseActor: bool,
+    pub noUseTarget: bool,
+    // Values beyond the ones we understand, kept so the transition can be written back out unchanged
+    pub extraValues: Option<Vec<String>>,
+}
+
+impl Transition {
+    /// Parse a transition from its file name (e.g. "33_32_LT.txt") and file contents
+    pub fn from_file(file_name: &str, contents: &str) -> Result<Self> {
+        let name_sections = file_name
+            .trim_end_matches(".txt")
+            .split('_')
+            .collect::<Vec<_>>();
+        if name_sections.len() < 2 {
+            return Err(anyhow!("Transition file name \"{file_name}\" is not in actor_target.txt form"));
+        }
+        let actorID = name_sections[0].parse().context(format!("Invalid actor ID in transition file name \"{file_name}\""))?;
+        let targetID = name_sections[1].parse().context(format!("Invalid target ID in transition file name \"{file_name}\""))?;
+        let (lastUseActor, lastUseTarget) = match name_sections.get(2) {
+            None => (false, false),
+            Some(&"LA") => (true, false),
+            // Older data names last-use-of-target transitions just _L
+            Some(&"LT") | Some(&"L") => (false, true),
+            Some(suffix) => return Err(anyhow!("Unknown transition file name suffix \"{suffix}\" in \"{file_name}\"")),
+        };
+
+        let values = contents.split_whitespace().collect::<Vec<_>>();
+        // Missing trailing values take the same defaults the game uses
+        let value = |index: usize| values.get(index).copied();
+        let newActorID = value(0).map_or(Ok(0), str::parse).context("Invalid newActor value")?;
+        let newTargetID = value(1).map_or(Ok(0), str::parse).context("Invalid newTarget value")?;
+        let autoDecaySeconds = value(2).map_or(Ok(0), str::parse).context("Invalid autoDecaySeconds value")?;
+        let actorMinUseFraction = value(3).map_or(Ok(0.0), str::parse).context("Invalid actorMinUseFraction value")?;
+        let targetMinUseFraction = value(4).map_or(Ok(0.0), str::parse).context("Invalid targetMinUseFraction value")?;
+        let reverseUseActor = value(5).is_some_and(|v| v != "0");
+        let reverseUseTarget = value(6).is_some_and(|v| v != "0");
+        let movement = value(7).map_or(Ok(MoveType::None), MoveType::from_str)?;
+        let desiredMoveDist = value(8).map_or(Ok(1), str::parse).context("Invalid desiredMoveDist value")?;
+        let noUseActor = value(9).is_some_and(|v| v != "0");
+        let noUseTarget = value(10).is_some_and(|v| v != "0");
+        let extraValues = if values.len() > 11 {
+            Some(values[11..].iter().map(|v| v.to_string()).collect())
+        } else {
+            None
+        };
+
+        Ok(Transition {
+            actorID,
+            targetID,
+            lastUseActor,
+            lastUseTarget,
+            newActorID,
+            newTargetID,
+            autoDecaySeconds,
+            actorMinUseFraction,
+            targetMinUseFraction,
+            reverseUseActor,
+            reverseUseTarget,
+            movement,
+            desiredMoveDist,
+            noUseActor,
+            noUseTarget,
+            extraValues,
+        })
+    }
+
+    /// The file name this transition is stored under in the transitions directory. A legacy _L file comes back as _LT.
+    pub fn file_name(&self) -> String {
+        // A transition is the last use of its actor or of its target, never both
+        let suffix = match (self.lastUseActor, self.lastUseTarget) {
+            (false, false) => "",
+            (true, _) => "_LA",
+            (false, true) => "_LT",
+        };
+        format!("{}_{}{}.txt", self.actorID, self.targetID, suffix)
+    }
+
+    /// Time passing (rather than a player action) causes this transition
+    pub fn is_decay(&self) -> bool {
+        self.actorID == -1
+    }
+
+    /// The player's bare hand is the actor
+    pub fn is_bare_hand(&self) -> bool {
+        self.actorID == 0
+    }
+
+    /// The actor is not consumed by the transition
+    pub fn is_tool(&self) -> bool {
+        self.actorID > 0 && self.actorID == self.newActorID
+    }
+
+    /// The target is not changed by the transition
+    pub fn target_remains(&self) -> bool {
+        self.targetID > 0 && self.targetID == self.newTargetID
+    }
+
+    /// autoDecaySeconds converted to seconds, or None if this transition doesn't decay
+    pub fn decay_seconds(&self) -> Option<u64> {
+        match self.autoDecaySeconds {
+            0 => None,
+            hours if hours < 0 => Some(hours.unsigned_abs() as u64 * 60 * 60),
+            seconds => Some(seconds as u64),
+        }
+    }
+}
+
+impl ToString for Transition {
+    fn to_string(&self) -> String {
+        let mut output = format!("{} {} {} {:.6} {:.6} {} {} {} {} {} {}",
+            self.newActorID,
+            self.newTargetID,
+            self.autoDecaySeconds,
+            self.actorMinUseFraction,
+            self.targetMinUseFraction,
+            self.reverseUseActor.to_i8(),
+            self.reverseUseTarget.to_i8(),
+            self.movement.to_i32(),
+            self.desiredMoveDist,
+            self.noUseActor.to_i8(),
+            self.noUseTarget.to_i8(),
+        );
+        if let Some(extraValues) = &self.extraValues {
+            for extraValue in extraValues {
+                output.push(' ');
+                output.push_str(extraValue);
+            }
+        }
+        output
+    }
+}
+
+/// All transitions, indexed by the object IDs that take part in them
+#[derive(Clone, Debug, Default)]
+pub struct TransitionGraph {
+    pub transitions: Vec<Transition>,
+    by_actor: HashMap<i32, Vec<usize>>,
+    by_target: HashMap<i32, Vec<usize>>,
+    by_product: HashMap<i32, Vec<usize>>,
+}
+
+impl TransitionGraph {
+    pub fn new(transitions: Vec<Transition>) -> Self {
+        let mut by_actor: HashMap<i32, Vec<usize>> = HashMap::new();
+        let mut by_target: HashMap<i32, Vec<usize>> = HashMap::new();
+        let mut by_product: HashMap<i32, Vec<usize>> = HashMap::new();
+        for (index, transition) in transitions.iter().enumerate() {
+            by_actor.entry(transition.actorID).or_default().push(index);
+            by_target.entry(transition.targetID).or_default().push(index);
+            by_product.entry(transition.newActorID).or_default().push(index);
+            if transition.newTargetID != transition.newActorID {
+                by_product.entry(transition.newTargetID).or_default().push(index);
+            }
+        }
+        TransitionGraph {
+            transitions,
+            by_actor,
+            by_target,
+            by_product,
+        }
+    }
+
+    fn lookup<'a>(&'a self, index: &'a HashMap<i32, Vec<usize>>, id: i32) -> impl Iterator<Item = &'a Transition> + 'a {
+        index
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.transitions[i])
+    }
+
+    /// Transitions where the object is held and used on something
+    pub fn transitions_with_actor(&self, id: i32) -> impl Iterator<Item = &Transition> + '_ {
+        self.lookup(&self.by_actor, id)
+    }
+
+    /// Transitions where something is used on the object
+    pub fn transitions_with_target(&self, id: i32) -> impl Iterator<Item = &Transition> + '_ {
+        self.lookup(&self.by_target, id)
+    }
+
+    /// Transitions that result in the object, either as the new actor or the new target
+    pub fn transitions_producing(&self, id: i32) -> impl Iterator<Item = &Transition> + '_ {
+        self.lookup(&self.by_product, id)
+    }
+
+    /// The transition the object goes through when left alone, if it decays
+    pub fn decay_transition(&self, id: i32) -> Option<&Transition> {
+        self.transitions_with_target(id)
+            .find(|transition| transition.is_decay() && !transition.lastUseTarget)
+    }
+
+    pub fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_VALUES: &str = "0 71 30 0.500000 0.000000 1 0 2 3 1 0";
+
+    #[test]
+    fn file_name_suffixes() {
+        let last_uses = |file_name: &str| {
+            let transition = Transition::from_file(file_name, ALL_VALUES).unwrap();
+            (transition.actorID, transition.targetID, transition.lastUseActor, transition.lastUseTarget)
+        };
+        assert_eq!(last_uses("33_32.txt"), (33, 32, false, false));
+        assert_eq!(last_uses("33_32_LA.txt"), (33, 32, true, false));
+        assert_eq!(last_uses("33_32_LT.txt"), (33, 32, false, true));
+        // Older data names last-use-of-target transitions just _L
+        assert_eq!(last_uses("33_32_L.txt"), (33, 32, false, true));
+        assert_eq!(last_uses("-1_32.txt"), (-1, 32, false, false));
+        assert!(Transition::from_file("33_32_LX.txt", ALL_VALUES).is_err());
+        assert!(Transition::from_file("33.txt", ALL_VALUES).is_err());
+    }
+
+    #[test]
+    fn file_names_are_written_back_out() {
+        for file_name in ["33_32.txt", "33_32_LA.txt", "33_32_LT.txt", "-1_32.txt"] {
+            assert_eq!(Transition::from_file(file_name, ALL_VALUES).unwrap().file_name(), file_name);
+        }
+        assert_eq!(Transition::from_file("33_32_L.txt", ALL_VALUES).unwrap().file_name(), "33_32_LT.txt");
+    }
+
+    #[test]
+    fn all_values() {
+        let transition = Transition::from_file("33_32.txt", ALL_VALUES).unwrap();
+        assert_eq!((transition.newActorID, transition.newTargetID, transition.autoDecaySeconds), (0, 71, 30));
+        assert_eq!((transition.actorMinUseFraction, transition.targetMinUseFraction), (0.5, 0.0));
+        assert_eq!((transition.reverseUseActor, transition.reverseUseTarget), (true, false));
+        assert_eq!((transition.movement.to_i32(), transition.desiredMoveDist), (2, 3));
+        assert_eq!((transition.noUseActor, transition.noUseTarget), (true, false));
+        assert_eq!(transition.extraValues, None);
+        assert_eq!(transition.to_string(), ALL_VALUES);
+    }
+
+    #[test]
+    fn missing_trailing_values_take_the_defaults() {
+        let transition = Transition::from_file("33_32.txt", "0 71").unwrap();
+        assert_eq!((transition.newActorID, transition.newTargetID, transition.autoDecaySeconds), (0, 71, 0));
+        assert_eq!((transition.actorMinUseFraction, transition.targetMinUseFraction), (0.0, 0.0));
+        assert_eq!((transition.reverseUseActor, transition.reverseUseTarget), (false, false));
+        assert_eq!((transition.movement.clone(), transition.desiredMoveDist), (MoveType::None, 1));
+        assert_eq!((transition.noUseActor, transition.noUseTarget), (false, false));
+        assert_eq!(transition.to_string(), "0 71 0 0.000000 0.000000 0 0 0 1 0 0");
+    }
+
+    #[test]
+    fn extra_trailing_values_are_kept() {
+        let transition = Transition::from_file("33_32.txt", &format!("{ALL_VALUES} 4 future")).unwrap();
+        assert_eq!(transition.extraValues, Some(vec!["4".to_string(), "future".to_string()]));
+        assert_eq!(transition.to_string(), format!("{ALL_VALUES} 4 future"));
+    }
+}
@@ -0,0 +1,157 @@
+#![allow(clippy::to_string_trait_impl)]
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use serde::Serialize;
+
+use crate::twotech_object::ClothingType;
+use crate::SharedGameObject;
+
+// Each body slot an outfit fills, in the order they're listed. Both feet take a shoe.
+const OUTFIT_SLOTS: [ClothingType; 6] = [
+    ClothingType::Head,
+    ClothingType::Top,
+    ClothingType::Bottom,
+    ClothingType::Shoe,
+    ClothingType::Shoe,
+    ClothingType::Pack,
+];
+
+/// What `Outfit::best` maximizes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutfitGoal {
+    // Total insulation, then slots
+    Insulation,
+    // Total numSlots, then slotSize, then insulation
+    Slots,
+    // insulation_weight * insulation + slots_weight * numSlots
+    Weighted { insulation_weight: f64, slots_weight: f64 },
+}
+
+impl FromStr for OutfitGoal {
+    type Err = anyhow::Error;
+
+    // Weighted goals start out with equal weights, for the caller to fill in
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "insulation" => Ok(OutfitGoal::Insulation),
+            "slots" | "storage" => Ok(OutfitGoal::Slots),
+            "weighted" | "mixed" => Ok(OutfitGoal::Weighted { insulation_weight: 1.0, slots_weight: 1.0 }),
+            _ => Err(anyhow!("Unknown outfit goal {s} (expected insulation, slots or weighted)"))
+        }
+    }
+}
+
+/// The best item for each body slot, out of a set of candidate objects
+#[derive(Clone, Debug, Serialize)]
+pub struct Outfit {
+    pub pieces: Vec<OutfitPiece>,
+    pub total_insulation: f64,
+    pub total_slots: i32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OutfitPiece {
+    pub slot: ClothingType,
+    pub id: String,
+    pub name: String,
+    pub insulation: f64,
+    pub num_slots: i32,
+    pub slot_size: Option<f32>,
+    pub depth: Option<i32>,
+}
+
+impl OutfitPiece {
+    fn new(slot: ClothingType, shared_obj: &SharedGameObject) -> Self {
+        let twotech = &shared_obj.twotech_data;
+        OutfitPiece {
+            slot,
+            id: twotech.id.clone(),
+            name: twotech.name.clone(),
+            // Computed from the OneLifeData7 rValue where possible, since twotech's can lag behind the game data
+            insulation: shared_obj.insulation().or(twotech.insulation).unwrap_or(0.0),
+            num_slots: twotech.numSlots.unwrap_or(0),
+            slot_size: twotech.slotSize,
+            depth: twotech.depth,
+        }
+    }
+
+    // Higher is better
+    fn compare(&self, other: &OutfitPiece, goal: OutfitGoal) -> Ordering {
+        let slot_size = |piece: &OutfitPiece| piece.slot_size.unwrap_or(0.0);
+        match goal {
+            OutfitGoal::Insulation => self.insulation
+                .total_cmp(&other.insulation)
+                .then(self.num_slots.cmp(&other.num_slots))
+                .then(slot_size(self).total_cmp(&slot_size(other))),
+            OutfitGoal::Slots => self.num_slots
+                .cmp(&other.num_slots)
+                .then(slot_size(self).total_cmp(&slot_size(other)))
+                .then(self.insulation.total_cmp(&other.insulation)),
+            OutfitGoal::Weighted { insulation_weight, slots_weight } => {
+                let score = |piece: &OutfitPiece| insulation_weight * piece.insulation + slots_weight * piece.num_slots as f64;
+                score(self).total_cmp(&score(other)).then(self.insulation.total_cmp(&other.insulation))
+            }
+        }
+    }
+}
+
+impl Outfit {
+    /// Pick the best candidate for each body slot. A piece of clothing only counts for one slot, so each slot
+    /// can be chosen on its own. Slots with no candidate are left empty, and shallower recipes win ties.
+    pub fn best<'a>(candidates: impl IntoIterator<Item = &'a SharedGameObject>, goal: OutfitGoal) -> Self {
+        let candidates = candidates
+            .into_iter()
+            .filter_map(|shared_obj| Some(OutfitPiece::new(shared_obj.twotech_data.clothing.clone()?, shared_obj)))
+            .collect::<Vec<_>>();
+        let pieces = OUTFIT_SLOTS
+            .iter()
+            .filter_map(|slot| {
+                candidates
+                    .iter()
+                    .filter(|piece| &piece.slot == slot)
+                    .max_by(|a, b| a.compare(b, goal).then(b.depth.cmp(&a.depth)))
+                    .cloned()
+            })
+            .collect::<Vec<_>>();
+        Outfit {
+            total_insulation: pieces.iter().map(|piece| piece.insulation).sum(),
+            total_slots: pieces.iter().map(|piece| piece.num_slots).sum(),
+            pieces,
+        }
+    }
+}
+
+impl ToString for Outfit {
+    fn to_string(&self) -> String {
+        let slot_name = |slot: &ClothingType| match slot {
+            ClothingType::Head => "Head",
+            ClothingType::Top => "Top",
+            ClothingType::Bottom => "Bottom",
+            ClothingType::Shoe => "Shoe",
+            ClothingType::Pack => "Pack",
+            _ => "Other",
+        };
+        let mut output = Vec::new();
+        for piece in &self.pieces {
+            let slots = match piece.slot_size {
+                Some(slot_size) if piece.num_slots > 0 => format!(", {} slots of size {slot_size}", piece.num_slots),
+                _ => String::new(),
+            };
+            output.push(format!(
+                "{:<7} {} ({}): {:.2}% insulation{slots}",
+                slot_name(&piece.slot),
+                piece.name,
+                piece.id,
+                piece.insulation * 100.0,
+            ));
+        }
+        if self.pieces.is_empty() {
+            output.push("No clothing matched".to_string());
+        }
+        output.push(format!("Total: {:.2}% insulation, {} slots", self.total_insulation * 100.0, self.total_slots));
+        output.join("\n")
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+
+use crate::filter_expression::lookup_field;
+use crate::query_results::QueryResults;
+use crate::SharedGameObject;
+
+// Table formats need some columns, so without --fields they get these
+const DEFAULT_TABLE_FIELDS: [&str; 2] = ["id", "name"];
+
+/// How the matching objects are written to the output file
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    // One line of JSON, an object keyed by object name (or an object of those, keyed by group)
+    #[default]
+    Json,
+    // The same, indented
+    PrettyJson,
+    // One JSON object per line
+    Ndjson,
+    Csv,
+    Tsv,
+    Markdown,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().replace(['-', '_'], "").as_str() {
+            "json" => Ok(OutputFormat::Json),
+            "prettyjson" | "pretty" => Ok(OutputFormat::PrettyJson),
+            "ndjson" | "jsonl" | "jsonlines" => Ok(OutputFormat::Ndjson),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            _ => Err(anyhow!("Unknown output format {s} (expected json, pretty-json, ndjson, csv, tsv or markdown)"))
+        }
+    }
+}
+
+/// Write query results out in the given format.
+///
+/// `fields` picks which fields to write, looked up the same way as in `--where` expressions (e.g. `numSlots`,
+/// `onelife.rValue`, `twotech.recipe.ingredients`). The JSON formats write whole objects when no fields are given,
+/// and the table formats fall back to the object's ID and name.
+///
+/// JSON is an object keyed by object name, in the order of the results, with the ID added to names that several
+/// objects share (e.g. `Stakes (12)`). With `json_list` it's a list of the objects instead.
+///
+/// Grouped results become an object keyed by group in JSON, one group per line in NDJSON, a leading column in CSV
+/// and TSV, and a heading and table per group in Markdown.
+pub fn format_objects<'a>(results: &QueryResults<'a>, format: OutputFormat, fields: &'a [String], json_list: bool) -> Result<String> {
+    let table_fields = if fields.is_empty() {
+        DEFAULT_TABLE_FIELDS.iter().map(|field| field.to_string()).collect()
+    } else {
+        fields.to_vec()
+    };
+    let rows = |objects: &[&SharedGameObject]| {
+        objects
+            .iter()
+            .map(|shared_obj| table_fields.iter().map(|field| cell_text(&lookup_field(shared_obj.as_json(), field))).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+    };
+    let json_rows = |objects: &[&'a SharedGameObject]| objects.iter().map(|shared_obj| JsonRow::new(shared_obj, fields)).collect::<Vec<_>>();
+
+    Ok(match format {
+        OutputFormat::Json | OutputFormat::PrettyJson => {
+            fn to_string(value: &impl Serialize, format: OutputFormat) -> serde_json::Result<String> {
+                match format {
+                    OutputFormat::PrettyJson => serde_json::to_string_pretty(value),
+                    _ => serde_json::to_string(value),
+                }
+            }
+            let json_objects = |objects: &[&'a SharedGameObject]| match json_list {
+                true => JsonObjects::List(json_rows(objects)),
+                false => JsonObjects::Keyed(keyed_json_rows(objects, fields)),
+            };
+            match results.is_grouped() {
+                true => {
+                    let groups = results.groups.iter().map(|group| (group.key.as_str(), json_objects(&group.objects))).collect::<Vec<_>>();
+                    to_string(&OrderedMap(&groups), format)?
+                }
+                false => to_string(&json_objects(&results.objects().collect::<Vec<_>>()), format)?,
+            }
+        }
+        OutputFormat::Ndjson => match results.is_grouped() {
+            true => results
+                .groups
+                .iter()
+                .map(|group| Ok(serde_json::to_string(&GroupLine { group: &group.key, objects: json_rows(&group.objects) })?))
+                .collect::<Result<Vec<_>>>()?
+                .join("\n"),
+            false => json_rows(&results.objects().collect::<Vec<_>>())
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<std::result::Result<Vec<_>, _>>()?
+                .join("\n"),
+        },
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            let (separator, escape): (&str, fn(&str) -> String) = match format {
+                OutputFormat::Csv => (",", csv_escape),
+                _ => ("\t", tsv_escape),
+            };
+            let mut lines = vec![results.group_by.iter().chain(&table_fields).cloned().collect::<Vec<_>>()];
+            for group in &results.groups {
+                for row in rows(&group.objects) {
+                    lines.push(results.group_by.iter().map(|_| group.key.clone()).chain(row).collect());
+                }
+            }
+            lines
+                .iter()
+                .map(|row| row.iter().map(|cell| escape(cell)).collect::<Vec<_>>().join(separator))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        OutputFormat::Markdown => {
+            let markdown_row = |row: &[String]| format!("| {} |", row.iter().map(|cell| cell.replace('|', "\\|").replace('\n', " ")).collect::<Vec<_>>().join(" | "));
+            let mut tables = Vec::new();
+            for group in &results.groups {
+                let mut lines = Vec::new();
+                if results.is_grouped() {
+                    lines.push(format!("## {}\n", group.key));
+                }
+                lines.push(markdown_row(&table_fields));
+                lines.push(markdown_row(&vec!["---".to_string(); table_fields.len()]));
+                lines.extend(rows(&group.objects).iter().map(|row| markdown_row(row)));
+                tables.push(lines.join("\n"));
+            }
+            tables.join("\n\n")
+        }
+    })
+}
+
+// An object as written in the JSON formats. Whole objects are written straight from the structs, so their fields stay
+// in the usual order.
+enum JsonRow<'a> {
+    Whole(&'a SharedGameObject),
+    Fields(Projection<'a>),
+}
+
+impl<'a> JsonRow<'a> {
+    fn new(shared_obj: &'a SharedGameObject, fields: &'a [String]) -> Self {
+        match fields.is_empty() {
+            true => JsonRow::Whole(shared_obj),
+            false => JsonRow::Fields(Projection::new(shared_obj.as_json(), fields)),
+        }
+    }
+}
+
+impl Serialize for JsonRow<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            JsonRow::Whole(shared_obj) => shared_obj.serialize(serializer),
+            JsonRow::Fields(projection) => projection.serialize(serializer),
+        }
+    }
+}
+
+// Objects keyed by name, like thol-helper has always written its JSON. Names several of the objects share get the
+// object's ID added, so none of them are dropped.
+fn keyed_json_rows<'a>(objects: &[&'a SharedGameObject], fields: &'a [String]) -> Vec<(String, JsonRow<'a>)> {
+    let mut name_counts = HashMap::new();
+    for shared_obj in objects {
+        *name_counts.entry(shared_obj.twotech_data.name.as_str()).or_insert(0) += 1;
+    }
+    objects
+        .iter()
+        .map(|shared_obj| {
+            let twotech = &shared_obj.twotech_data;
+            let key = match name_counts[twotech.name.as_str()] {
+                1 => twotech.name.clone(),
+                _ => format!("{} ({})", twotech.name, twotech.id),
+            };
+            (key, JsonRow::new(shared_obj, fields))
+        })
+        .collect()
+}
+
+// The objects written in the JSON formats, keyed or as a list
+enum JsonObjects<'a> {
+    Keyed(Vec<(String, JsonRow<'a>)>),
+    List(Vec<JsonRow<'a>>),
+}
+
+impl Serialize for JsonObjects<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            JsonObjects::Keyed(rows) => OrderedMap(rows).serialize(serializer),
+            JsonObjects::List(rows) => rows.serialize(serializer),
+        }
+    }
+}
+
+// One line of grouped NDJSON
+#[derive(Serialize)]
+struct GroupLine<'a> {
+    group: &'a str,
+    objects: Vec<JsonRow<'a>>,
+}
+
+// A JSON object with its keys in the given order, rather than sorted like serde_json's Map
+struct OrderedMap<'a, K, V>(&'a [(K, V)]);
+
+impl<K: Serialize, V: Serialize> Serialize for OrderedMap<'_, K, V> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+// The chosen fields of an object, serialized as a JSON object with the fields in the order they were asked for
+struct Projection<'a> {
+    fields: &'a [String],
+    values: Vec<Value>,
+}
+
+impl<'a> Projection<'a> {
+    fn new(object: &Value, fields: &'a [String]) -> Self {
+        Projection { fields, values: fields.iter().map(|field| lookup_field(object, field)).collect() }
+    }
+}
+
+impl Serialize for Projection<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+        for (field, value) in self.fields.iter().zip(&self.values) {
+            map.serialize_entry(field, value)?;
+        }
+        map.end()
+    }
+}
+
+// Strings as-is, lists joined with commas, and anything nested as JSON. Missing values are empty.
+pub(crate) fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Array(elements) if elements.iter().all(|element| !element.is_array() && !element.is_object()) => {
+            elements.iter().map(cell_text).collect::<Vec<_>>().join(",")
+        }
+        value => value.to_string(),
+    }
+}
+
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+// TSV has no quoting, so separators inside a cell become spaces
+fn tsv_escape(cell: &str) -> String {
+    cell.replace(['\t', '\n', '\r'], " ")
+}
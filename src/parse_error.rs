@@ -0,0 +1,86 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Why a OneLifeData7 object file couldn't be parsed, and exactly where
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParseError {
+    // None if the id line itself couldn't be parsed
+    pub object_id: Option<i32>,
+    // 1-based line number in the file
+    pub line_number: Option<usize>,
+    pub field: Option<String>,
+    // The offending line
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        ParseError {
+            object_id: None,
+            line_number: None,
+            field: None,
+            text: String::new(),
+            message: message.into(),
+        }
+    }
+
+    pub fn at_line(mut self, line_number: usize, text: &str) -> Self {
+        self.line_number = Some(line_number);
+        self.text = text.to_string();
+        self
+    }
+
+    pub fn with_field(mut self, field: &str) -> Self {
+        self.field = Some(field.to_string());
+        self
+    }
+
+    pub fn with_object_id(mut self, object_id: i32) -> Self {
+        self.object_id = Some(object_id);
+        self
+    }
+
+    /// Shift the line number, for errors found while parsing a block that starts part-way into the file
+    pub fn offset_lines(mut self, offset: usize) -> Self {
+        self.line_number = self.line_number.map(|n| n + offset);
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(object_id) = self.object_id {
+            write!(f, "object {object_id}, ")?;
+        }
+        if let Some(line_number) = self.line_number {
+            write!(f, "line {line_number}, ")?;
+        }
+        if let Some(field) = &self.field {
+            write!(f, "field {field}, ")?;
+        }
+        write!(f, "{}", self.message)?;
+        if !self.text.is_empty() {
+            write!(f, " (\"{}\")", self.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A file that failed to parse, kept so the loader can report every failure at once.
+/// Cached along with the objects that did parse, so the failures are reported on every run, not just the first.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParseFailure {
+    pub path: PathBuf,
+    pub error: ParseError,
+}
+
+impl fmt::Display for ParseFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.to_string_lossy(), self.error)
+    }
+}
@@ -0,0 +1,167 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use serde_json::Value;
+
+use crate::filter_expression::{as_number, lookup_field};
+use crate::output_format::cell_text;
+use crate::SharedGameObject;
+
+// The group for objects that don't have the field being grouped by
+const MISSING_GROUP: &str = "none";
+
+/// A field to sort results by, given with `--sort-by`: `insulation`, `insulation:desc` or `-insulation`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SortKey {
+    pub field: String,
+    pub descending: bool,
+}
+
+impl FromStr for SortKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        let (field, descending) = match s.rsplit_once(':') {
+            Some((field, direction)) => match direction.to_lowercase().as_str() {
+                "asc" | "ascending" => (field, false),
+                "desc" | "descending" => (field, true),
+                _ => return Err(anyhow!("Unknown sort direction {direction} in {s} (expected asc or desc)")),
+            },
+            None => match s.strip_prefix('-') {
+                Some(field) => (field, true),
+                None => (s, false),
+            },
+        };
+        if field.is_empty() {
+            return Err(anyhow!("Missing field to sort by in {s}"));
+        }
+        Ok(SortKey { field: field.to_string(), descending })
+    }
+}
+
+/// Objects that share a value of the field results are grouped by
+#[derive(Clone, Debug)]
+pub struct ResultGroup<'a> {
+    // The field's value as text, or "none" if the objects don't have it. Empty when results aren't grouped.
+    pub key: String,
+    pub objects: Vec<&'a SharedGameObject>,
+}
+
+/// Matching objects, sorted, limited and grouped as asked
+#[derive(Clone, Debug)]
+pub struct QueryResults<'a> {
+    // The field objects are grouped by. Without one, all the objects are in a single group.
+    pub group_by: Option<String>,
+    pub groups: Vec<ResultGroup<'a>>,
+}
+
+impl<'a> QueryResults<'a> {
+    /// Objects are sorted by each key in turn, and then by name and ID. Objects missing a field sort after the
+    /// ones that have it, in either direction. Groups are sorted by their value, and `limit` applies to each group.
+    pub fn new(
+        objects: impl IntoIterator<Item = &'a SharedGameObject>,
+        sort_by: &[SortKey],
+        group_by: Option<&str>,
+        limit: Option<usize>,
+    ) -> Self {
+        let mut objects = objects.into_iter().collect::<Vec<_>>();
+        objects.sort_by(|a, b| a.twotech_data.name.cmp(&b.twotech_data.name).then(compare_ids(&a.twotech_data.id, &b.twotech_data.id)));
+        if !sort_by.is_empty() {
+            // Sorting is stable, so objects that tie on every key stay in name order
+            let mut keyed = objects
+                .into_iter()
+                .map(|shared_obj| (sort_by.iter().map(|key| lookup_field(shared_obj.as_json(), &key.field)).collect::<Vec<_>>(), shared_obj))
+                .collect::<Vec<_>>();
+            keyed.sort_by(|(a, _), (b, _)| {
+                sort_by
+                    .iter()
+                    .zip(a.iter().zip(b))
+                    .map(|(key, (a, b))| match (a, b) {
+                        (Value::Null, _) | (_, Value::Null) => compare_values(a, b),
+                        _ if key.descending => compare_values(b, a),
+                        _ => compare_values(a, b),
+                    })
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            });
+            objects = keyed.into_iter().map(|(_, shared_obj)| shared_obj).collect();
+        }
+
+        let mut groups = match group_by {
+            None => vec![(Value::Null, ResultGroup { key: String::new(), objects })],
+            Some(group_by) => {
+                let mut groups: Vec<(Value, ResultGroup)> = Vec::new();
+                let mut group_indexes = HashMap::new();
+                for shared_obj in objects {
+                    let value = lookup_field(shared_obj.as_json(), group_by);
+                    let key = match value {
+                        Value::Null => MISSING_GROUP.to_string(),
+                        _ => cell_text(&value),
+                    };
+                    let index = *group_indexes.entry(key.clone()).or_insert_with(|| {
+                        groups.push((value, ResultGroup { key, objects: Vec::new() }));
+                        groups.len() - 1
+                    });
+                    groups[index].1.objects.push(shared_obj);
+                }
+                groups.sort_by(|(a, _), (b, _)| compare_values(a, b));
+                groups
+            }
+        };
+        if let Some(limit) = limit {
+            groups.iter_mut().for_each(|(_, group)| group.objects.truncate(limit));
+        }
+        QueryResults {
+            group_by: group_by.map(str::to_string),
+            groups: groups.into_iter().map(|(_, group)| group).collect(),
+        }
+    }
+
+    pub fn is_grouped(&self) -> bool {
+        self.group_by.is_some()
+    }
+
+    /// Every object, group by group
+    pub fn objects(&self) -> impl Iterator<Item = &'a SharedGameObject> + '_ {
+        self.groups.iter().flat_map(|group| group.objects.iter().copied())
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.iter().map(|group| group.objects.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// Numbers (including numeric strings, like IDs) by value, lists element by element, and anything else as
+// case-insensitive text. Missing values go last.
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Greater,
+        (_, Value::Null) => Ordering::Less,
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare_values(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(a.len().cmp(&b.len())),
+        _ => match (as_number(a), as_number(b)) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            _ => cell_text(a).to_lowercase().cmp(&cell_text(b).to_lowercase()),
+        },
+    }
+}
+
+// Object IDs are numbers stored as strings
+fn compare_ids(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
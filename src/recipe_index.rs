@@ -0,0 +1,215 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::SharedGameObject;
+
+/// Reverse index over every object's recipe: for each object ID, the objects made directly from it.
+///
+/// Each recipe step makes its `id` from its actor and target, so both get an edge to the step's result.
+/// Any of the recipe's `ingredients` that no step uses (or every ingredient, when there are no steps) gets an edge
+/// straight to the object itself.
+/// Edges are collected across all recipes, so an intermediate that only shows up inside another
+/// object's recipe is still linked.
+#[derive(Clone, Debug, Default)]
+pub struct RecipeIndex {
+    made_from: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// An object whose recipe tree contains the queried ingredient
+#[derive(Clone, Debug, PartialEq)]
+pub struct UsedIn {
+    pub product_id: String,
+    // Number of crafting steps between the ingredient and the product
+    pub depth: usize,
+    // Object IDs from the ingredient to the product, both included
+    pub path: Vec<String>,
+}
+
+impl RecipeIndex {
+    pub fn new(objects: &BTreeMap<String, SharedGameObject>) -> Self {
+        let mut made_from = BTreeMap::<String, BTreeSet<String>>::new();
+        for (id, obj) in objects {
+            let Some(recipe) = &obj.twotech_data.recipe else {
+                continue;
+            };
+            let mut step_inputs = HashSet::new();
+            for step in recipe.steps.iter().flatten().flatten() {
+                let Some(result_id) = &step.id else {
+                    continue;
+                };
+                for input_id in [&step.actorID, &step.targetID].into_iter().flatten() {
+                    step_inputs.insert(input_id);
+                    if input_id != result_id {
+                        made_from.entry(input_id.clone()).or_default().insert(result_id.clone());
+                    }
+                }
+            }
+            for ingredient in recipe.ingredients.iter().flatten().filter(|ingredient| *ingredient != id && !step_inputs.contains(ingredient)) {
+                made_from.entry(ingredient.clone()).or_default().insert(id.clone());
+            }
+        }
+        RecipeIndex { made_from }
+    }
+
+    /// Objects made directly (in a single step) from the given object
+    pub fn made_directly_from(&self, id: &str) -> impl Iterator<Item = &String> {
+        self.made_from.get(id).into_iter().flatten()
+    }
+
+    /// Every object reachable from any of the starting IDs by following recipe steps, with the shortest path to it.
+    /// Sorted by depth, then ID. Objects more than `max_depth` steps away are left out.
+    pub fn used_in(&self, start_ids: &[String], max_depth: Option<usize>) -> Vec<UsedIn> {
+        // Breadth-first, so the first path found to each product is a shortest one
+        let mut previous = BTreeMap::<&str, Option<&str>>::new();
+        let mut queue = VecDeque::new();
+        for id in start_ids {
+            previous.insert(id, None);
+            queue.push_back((id.as_str(), 0));
+        }
+        let mut results = Vec::new();
+        while let Some((id, depth)) = queue.pop_front() {
+            if depth > 0 {
+                let mut path = vec![id.to_string()];
+                let mut current = id;
+                while let Some(Some(previous_id)) = previous.get(current) {
+                    path.push(previous_id.to_string());
+                    current = previous_id;
+                }
+                path.reverse();
+                results.push(UsedIn { product_id: id.to_string(), depth, path });
+            }
+            if max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                continue;
+            }
+            for product_id in self.made_directly_from(id) {
+                if !previous.contains_key(product_id.as_str()) {
+                    previous.insert(product_id, Some(id));
+                    queue.push_back((product_id, depth + 1));
+                }
+            }
+        }
+        results.sort_by_key(|used_in| (used_in.depth, used_in.product_id.parse::<i32>().ok()));
+        results
+    }
+}
+
+/// Every object's full set of recursive ingredients, so ingredient filters are a single lookup per object.
+///
+/// An object's direct ingredients are its recipe's `ingredients` plus every actor and target in its recipe steps
+/// (an empty hand counts as -1), and the closure follows those through the recipes of any ingredient that is itself
+/// a known object. This matches what `GameDatabase::find_target_ingredient` walks one object at a time.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct IngredientIndex {
+    ingredients: BTreeMap<String, HashSet<String>>,
+}
+
+impl IngredientIndex {
+    pub fn new(objects: &BTreeMap<String, SharedGameObject>) -> Self {
+        let direct_ingredients = objects
+            .iter()
+            .map(|(id, obj)| {
+                let recipe = obj.twotech_data.recipe.as_ref();
+                let step_ingredients = recipe
+                    .and_then(|recipe| recipe.steps.as_ref())
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .flat_map(|step| [step.actorID.clone().unwrap_or("-1".to_string()), step.targetID.clone().unwrap_or("-1".to_string())]);
+                let ingredients = recipe
+                    .and_then(|recipe| recipe.ingredients.clone())
+                    .into_iter()
+                    .flatten()
+                    .chain(step_ingredients)
+                    .collect::<HashSet<_>>();
+                (id.as_str(), ingredients)
+            })
+            .collect::<BTreeMap<_, _>>();
+        // Recipes can loop back on themselves, so each object gets its own walk rather than reusing other objects' results
+        let ingredients = direct_ingredients
+            .par_iter()
+            .map(|(&id, _)| {
+                let mut all_ingredients = HashSet::new();
+                let mut stack = vec![id];
+                let mut visited = HashSet::from([id]);
+                while let Some(current) = stack.pop() {
+                    for ingredient in direct_ingredients.get(current).into_iter().flatten() {
+                        all_ingredients.insert(ingredient.clone());
+                        if direct_ingredients.contains_key(ingredient.as_str()) && visited.insert(ingredient.as_str()) {
+                            stack.push(ingredient.as_str());
+                        }
+                    }
+                }
+                (id.to_string(), all_ingredients)
+            })
+            .collect();
+        IngredientIndex { ingredients }
+    }
+
+    /// Whether the ingredient appears anywhere in the object's recipe tree. Every object counts as containing itself.
+    pub fn contains(&self, object_id: &str, ingredient_id: &str) -> bool {
+        object_id == ingredient_id || self.ingredients.get(object_id).is_some_and(|ingredients| ingredients.contains(ingredient_id))
+    }
+
+    pub fn ingredients_of(&self, object_id: &str) -> Option<&HashSet<String>> {
+        self.ingredients.get(object_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_fixtures::game_database;
+
+    fn used_in(index: &RecipeIndex, id: &str, max_depth: Option<usize>) -> Vec<(String, usize, Vec<String>)> {
+        index
+            .used_in(&[id.to_string()], max_depth)
+            .into_iter()
+            .map(|used_in| (used_in.product_id, used_in.depth, used_in.path))
+            .collect()
+    }
+
+    fn path(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn used_in_follows_recipe_steps_and_ingredients() {
+        // The knife is made from a stone and a flint in one step, then cuts a log into a stake.
+        // The stake's recipe also lists rope as an ingredient that none of its steps name.
+        let game_database = game_database(
+            &[
+                json!({"id": "1", "name": "Stone"}),
+                json!({"id": "2", "name": "Flint"}),
+                json!({"id": "3", "name": "Knife", "recipe": {"ingredients": ["1", "2"], "steps": [[{"id": "3", "actorID": "1", "targetID": "2"}]]}}),
+                json!({"id": "4", "name": "Log"}),
+                json!({"id": "5", "name": "Rope"}),
+                json!({"id": "6", "name": "Stake", "recipe": {
+                    "ingredients": ["1", "2", "4", "5"],
+                    "steps": [[{"id": "3", "actorID": "1", "targetID": "2"}], [{"id": "6", "actorID": "3", "targetID": "4"}]],
+                }}),
+                json!({"id": "7", "name": "Bundle of Stakes", "recipe": {"ingredients": ["6", "6"]}}),
+            ],
+            &[],
+        );
+        let index = &game_database.recipe_index;
+
+        assert_eq!(
+            used_in(index, "1", None),
+            [
+                ("3".to_string(), 1, path(&["1", "3"])),
+                ("6".to_string(), 2, path(&["1", "3", "6"])),
+                ("7".to_string(), 3, path(&["1", "3", "6", "7"])),
+            ]
+        );
+        assert_eq!(used_in(index, "1", Some(1)), [("3".to_string(), 1, path(&["1", "3"]))]);
+        assert_eq!(
+            used_in(index, "5", None),
+            [("6".to_string(), 1, path(&["5", "6"])), ("7".to_string(), 2, path(&["5", "6", "7"]))]
+        );
+        assert_eq!(used_in(index, "7", None), []);
+    }
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+
+use crate::one_life_data_object::OneLifeDataObject;
+use crate::parse_error::ParseFailure;
+
+// Lines of unchanged context shown around each change, same as `diff -u`
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// A file whose re-serialized contents don't match the original
+#[derive(Clone, Debug)]
+pub struct RoundtripMismatch {
+    pub path: PathBuf,
+    pub diff: String,
+}
+
+/// The result of parsing and re-serializing every OneLifeData7 object file
+#[derive(Clone, Debug, Default)]
+pub struct RoundtripReport {
+    pub files_checked: usize,
+    pub mismatches: Vec<RoundtripMismatch>,
+    // Files that couldn't be parsed at all
+    pub parse_failures: Vec<ParseFailure>,
+}
+
+impl RoundtripReport {
+    pub fn is_lossless(&self) -> bool {
+        self.mismatches.is_empty() && self.parse_failures.is_empty()
+    }
+}
+
+/// Parse every objects/*.txt file in the OneLifeData7 directory, write it back out with
+/// `OneLifeDataObject::to_string`, and diff the result against the original file.
+/// Trailing whitespace at the end of the file is ignored.
+pub fn verify_object_roundtrip(one_life_data_directory: &str) -> Result<RoundtripReport> {
+    let object_directory = PathBuf::from(one_life_data_directory).join("objects");
+    let object_file_regex = regex::Regex::new(r"^\d+\.txt$").unwrap();
+    let mut paths = fs::read_dir(&object_directory)
+        .context(format!("Could not read OneLifeData7 objects directory ({})", object_directory.to_string_lossy()))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().is_some_and(|name| object_file_regex.is_match(&name.to_string_lossy())))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let mut report = RoundtripReport::default();
+    for path in paths {
+        report.files_checked += 1;
+        let contents = fs::read_to_string(&path).context(format!("Could not read {}", path.to_string_lossy()))?;
+        let original = contents.trim_end();
+        match OneLifeDataObject::from_str(original) {
+            Ok(object) => {
+                let regenerated = object.to_string();
+                if regenerated != original {
+                    let diff = unified_diff(&path.to_string_lossy(), original, &regenerated);
+                    report.mismatches.push(RoundtripMismatch { path, diff });
+                }
+            }
+            Err(error) => report.parse_failures.push(ParseFailure { path, error }),
+        }
+    }
+    Ok(report)
+}
+
+/// A unified diff (like `diff -u`) between the original and regenerated file contents
+pub fn unified_diff(file_name: &str, original: &str, regenerated: &str) -> String {
+    // Each line, tagged with ' ', '-' or '+', and its line number in the original and regenerated text
+    let mut diff_lines = Vec::new();
+    let (mut original_line, mut regenerated_line) = (1, 1);
+    for result in diff::lines(original, regenerated) {
+        match result {
+            diff::Result::Both(line, _) => {
+                diff_lines.push((' ', line, original_line, regenerated_line));
+                original_line += 1;
+                regenerated_line += 1;
+            }
+            diff::Result::Left(line) => {
+                diff_lines.push(('-', line, original_line, regenerated_line));
+                original_line += 1;
+            }
+            diff::Result::Right(line) => {
+                diff_lines.push(('+', line, original_line, regenerated_line));
+                regenerated_line += 1;
+            }
+        }
+    }
+
+    let mut output = vec![
+        format!("--- {file_name} (original)"),
+        format!("+++ {file_name} (regenerated)"),
+    ];
+    let changed_indexes = diff_lines
+        .iter()
+        .enumerate()
+        .filter(|(_, (tag, ..))| *tag != ' ')
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let mut changed_iter = changed_indexes.iter().peekable();
+    while let Some(&first_change) = changed_iter.next() {
+        // Grow the hunk while the next change is close enough that the context would overlap
+        let mut last_change = first_change;
+        while let Some(&&next_change) = changed_iter.peek() {
+            if next_change - last_change > DIFF_CONTEXT_LINES * 2 {
+                break;
+            }
+            last_change = next_change;
+            changed_iter.next();
+        }
+        let start = first_change.saturating_sub(DIFF_CONTEXT_LINES);
+        let end = (last_change + DIFF_CONTEXT_LINES + 1).min(diff_lines.len());
+        let hunk = &diff_lines[start..end];
+        let original_count = hunk.iter().filter(|(tag, ..)| *tag != '+').count();
+        let regenerated_count = hunk.iter().filter(|(tag, ..)| *tag != '-').count();
+        let (_, _, original_start, regenerated_start) = hunk[0];
+        output.push(format!("@@ -{},{} +{},{} @@", original_start, original_count, regenerated_start, regenerated_count));
+        for (tag, line, ..) in hunk {
+            output.push(format!("{tag}{line}"));
+        }
+    }
+    output.join("\n")
+}
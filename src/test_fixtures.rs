@@ -0,0 +1,35 @@
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+
+use crate::game_database::GameDatabase;
+use crate::one_life_data_object::OneLifeDataObject;
+use crate::one_life_data_transition::{Transition, TransitionGraph};
+use crate::twotech_object::TwoTechObject;
+
+/// A game database with a few hand-written objects and transitions, for tests.
+///
+/// Objects are written like twotech's object files (they need an `id` and a `name`). An `onelife` key holds the
+/// OneLifeData7 fields, e.g. `"onelife": {"numUses": {"numUses": 5, "useChance": 0.5}}`, and the OneLifeData7 object
+/// gets the same ID and name. Transitions are a file name and contents, like in OneLifeData7's transitions directory.
+pub(crate) fn game_database(objects: &[Value], transitions: &[(&str, &str)]) -> GameDatabase {
+    let mut one_life_objects = BTreeMap::new();
+    let mut twotech_objects = BTreeMap::new();
+    for object in objects {
+        let mut twotech = object.clone();
+        let one_life = twotech.as_object_mut().unwrap().remove("onelife").unwrap_or(json!({}));
+        let id = twotech["id"].as_str().unwrap().to_string();
+        let mut one_life_object = json!({"id": id.parse::<i32>().unwrap(), "name": twotech["name"]});
+        one_life_object.as_object_mut().unwrap().extend(one_life.as_object().unwrap().clone());
+        one_life_objects.insert(id.clone(), serde_json::from_value::<OneLifeDataObject>(one_life_object).unwrap());
+        twotech_objects.insert(id, serde_json::from_value::<TwoTechObject>(twotech).unwrap());
+    }
+    let mut game_database = GameDatabase::from_parts(one_life_objects, twotech_objects);
+    game_database.transitions = TransitionGraph::new(
+        transitions
+            .iter()
+            .map(|(file_name, contents)| Transition::from_file(file_name, contents).unwrap())
+            .collect(),
+    );
+    game_database
+}
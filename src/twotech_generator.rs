@@ -0,0 +1,432 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::one_life_data_category::{expand_category, CategoryData};
+use crate::one_life_data_object::OneLifeDataObject;
+use crate::one_life_data_transition::{Transition, TransitionGraph};
+use crate::twotech_object::{
+    Biome, ClothingType, DecayTime, MoveType, ObjectRecipe, RecipeStep, TechTreeNode, TransitionAwayData,
+    TransitionTimedData, TransitionTowardData, TwoTechObject,
+};
+
+// Guard against recipe trees that explode because the same consumed intermediate is used many times
+const MAX_RECIPE_STEPS: usize = 1000;
+
+/// Computes the same data twotech's Node build produces (depth, craftable, recipe, techTree and
+/// the transition lists), directly from OneLifeData7 objects and transitions.
+pub struct TwoTechGenerator<'a> {
+    one_life_objects: &'a BTreeMap<String, OneLifeDataObject>,
+    // With category objects expanded into their members
+    transitions: TransitionGraph,
+    depths: HashMap<i32, i32>,
+    // The lowest-depth transition producing each object, as an index into `transitions`
+    best_transitions: HashMap<i32, usize>,
+}
+
+impl<'a> TwoTechGenerator<'a> {
+    pub fn new(one_life_objects: &'a BTreeMap<String, OneLifeDataObject>, transitions: &TransitionGraph, categories: &BTreeMap<i32, CategoryData>) -> Self {
+        let mut generator = TwoTechGenerator {
+            one_life_objects,
+            transitions: TransitionGraph::new(expand_category_transitions(&transitions.transitions, categories)),
+            depths: HashMap::new(),
+            best_transitions: HashMap::new(),
+        };
+        generator.calculate_depths();
+        generator
+    }
+
+    fn is_natural(&self, id: i32) -> bool {
+        self.one_life_objects
+            .get(&id.to_string())
+            .and_then(|o| o.mapChance.as_ref())
+            .is_some_and(|m| m.mapChance > 0.0)
+    }
+
+    // Hand (0), ground/time (-1) and default (-2) are always available
+    fn available_depth(&self, id: i32) -> Option<i32> {
+        if id <= 0 {
+            Some(0)
+        } else {
+            self.depths.get(&id).copied()
+        }
+    }
+
+    /// Natural objects have depth 0, and every other object is one deeper than the deepest input
+    /// of the shallowest transition producing it. Iterate until no depth improves.
+    fn calculate_depths(&mut self) {
+        for id in self.one_life_objects.values().map(|o| o.id) {
+            if self.is_natural(id) {
+                self.depths.insert(id, 0);
+            }
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (index, transition) in self.transitions.transitions.iter().enumerate() {
+                let (Some(actor_depth), Some(target_depth)) = (self.available_depth(transition.actorID), self.available_depth(transition.targetID)) else {
+                    continue;
+                };
+                let depth = actor_depth.max(target_depth) + 1;
+                for product in [transition.newActorID, transition.newTargetID] {
+                    if product <= 0 || product == transition.actorID || product == transition.targetID {
+                        continue;
+                    }
+                    if self.depths.get(&product).is_none_or(|&d| depth < d) {
+                        self.depths.insert(product, depth);
+                        self.best_transitions.insert(product, index);
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn depth(&self, id: i32) -> Option<i32> {
+        self.depths.get(&id).copied()
+    }
+
+    pub fn is_craftable(&self, id: i32) -> bool {
+        !self.is_natural(id) && self.depths.contains_key(&id)
+    }
+
+    /// Generate a TwoTechObject for every OneLifeData7 object, keyed by object ID
+    pub fn generate(&self) -> BTreeMap<String, TwoTechObject> {
+        self.one_life_objects
+            .iter()
+            .map(|(key, obj)| (key.clone(), self.generate_object(obj)))
+            .collect()
+    }
+
+    pub fn generate_object(&self, obj: &OneLifeDataObject) -> TwoTechObject {
+        let id = obj.id;
+        let craftable = self.is_craftable(id);
+        let moving_decay = self.transitions
+            .decay_transition(id)
+            .filter(|t| t.movement != MoveType::None);
+        let num_slots = obj.numSlots.as_ref().map(|n| n.numSlots).filter(|&n| n > 0);
+        TwoTechObject {
+            id: id.to_string(),
+            name: obj.name.clone(),
+            recipe: if craftable { Some(self.recipe(id)) } else { None },
+            speedMult: obj.speedMult.map(f64::from),
+            version: None,
+            blocksWalking: obj.blocksWalking.as_ref().map(|b| b.blocksWalking),
+            deadlyDistance: obj.deadlyDistance.filter(|&d| d > 0),
+            biomes: obj.mapChance.as_ref()
+                .filter(|m| m.mapChance > 0.0)
+                .and_then(|m| m.biomes.as_ref().map(|biomes| {
+                    biomes.iter()
+                        .map(|biome| Biome { id: Some(biome.to_u8().to_string()), spawnChance: Some(m.mapChance as f64) })
+                        .collect()
+                })),
+            minPickupAge: obj.permanent.as_ref().and_then(|p| p.minPickupAge),
+            transitionsTimed: non_empty(self.transitions_timed(id)),
+            transitionsToward: non_empty(self.transitions_toward(id)),
+            craftable: Some(craftable),
+            clothing: obj.clothing.as_ref()
+                .and_then(|c| c.clothing.to_string().parse::<ClothingType>().ok())
+                .filter(|c| c != &ClothingType::None),
+            heatValue: obj.heatValue.filter(|&h| h != 0),
+            mapChance: obj.mapChance.as_ref().map(|m| m.mapChance as f64).filter(|&m| m > 0.0),
+            moveType: moving_decay.map(|t| t.movement.clone()),
+            numSlots: num_slots,
+            numUses: obj.numUses.as_ref().map(|n| n.numUses).filter(|&n| n > 1),
+            useDistance: obj.useDistance,
+            depth: self.depth(id),
+            foodValue: obj.foodValue.clone().filter(|f| f.iter().any(|&v| v > 0)),
+            insulation: obj.insulation(),
+            size: obj.containSize.as_ref().map(|c| c.containSize),
+            sounds: None,
+            useChance: obj.numUses.as_ref().and_then(|n| n.useChance).map(f64::from).filter(|&u| u > 0.0),
+            techTree: if craftable { non_empty(self.tech_tree(id, &mut HashSet::new())) } else { None },
+            moveDistance: moving_decay.map(|t| t.desiredMoveDist),
+            transitionsAway: non_empty(self.transitions_away(id)),
+            slotSize: num_slots.and(obj.slotSize),
+        }
+    }
+
+    /// Expand the object's shallowest transitions back to natural objects.
+    /// The target of each transition continues the main branch, while the actor starts a side branch.
+    pub fn recipe(&self, id: i32) -> ObjectRecipe {
+        let mut steps = Vec::new();
+        let mut ingredients = Vec::new();
+        let mut built_tools = HashSet::new();
+        self.expand_recipe(id, true, &mut Vec::new(), &mut built_tools, &mut steps, &mut ingredients);
+
+        // Group steps into rows of equal depth, shallowest (first to do) first
+        let mut rows: BTreeMap<i32, Vec<RecipeStep>> = BTreeMap::new();
+        for step in steps {
+            rows.entry(step.depth.unwrap_or(0)).or_default().push(step);
+        }
+        ingredients.sort_by_key(|i: &String| i.parse::<i32>().unwrap_or(0));
+        ObjectRecipe {
+            steps: non_empty(rows.into_values().collect()),
+            ingredients: non_empty(ingredients),
+        }
+    }
+
+    // The lowest-depth transition producing the object
+    fn best_transition(&self, id: i32) -> Option<&Transition> {
+        self.best_transitions.get(&id).map(|&index| &self.transitions.transitions[index])
+    }
+
+    fn expand_recipe(&self, id: i32, main_branch: bool, ancestors: &mut Vec<i32>, built_tools: &mut HashSet<i32>, steps: &mut Vec<RecipeStep>, ingredients: &mut Vec<String>) {
+        let transition = match self.best_transition(id) {
+            Some(transition) if !self.is_natural(id) && steps.len() < MAX_RECIPE_STEPS => transition,
+            _ => {
+                ingredients.push(id.to_string());
+                return;
+            }
+        };
+        ancestors.push(id);
+        steps.push(RecipeStep {
+            id: Some(id.to_string()),
+            mainBranch: Some(main_branch),
+            depth: self.depth(id),
+            actorID: Some(transition.actorID.to_string()).filter(|_| transition.actorID > 0),
+            actorUses: None,
+            hand: Some(transition.is_bare_hand()),
+            uses: None,
+            targetID: Some(transition.targetID.to_string()).filter(|_| transition.targetID > 0),
+            targetUses: None,
+        });
+        if transition.targetID > 0 && !ancestors.contains(&transition.targetID) {
+            self.expand_recipe(transition.targetID, main_branch, ancestors, built_tools, steps, ingredients);
+        }
+        // Tools aren't consumed, so they only need to be made once per recipe
+        if transition.actorID > 0 && !ancestors.contains(&transition.actorID) && (!transition.is_tool() || built_tools.insert(transition.actorID)) {
+            self.expand_recipe(transition.actorID, false, ancestors, built_tools, steps, ingredients);
+        }
+        ancestors.pop();
+    }
+
+    /// Every craftable tool used while making the object, each with its own tools as child nodes
+    fn tech_tree(&self, id: i32, visited: &mut HashSet<i32>) -> Vec<TechTreeNode> {
+        visited.insert(id);
+        let mut tools = Vec::new();
+        self.collect_tools(id, &mut HashSet::new(), &mut tools);
+        let tools = tools.into_iter()
+            .filter(|tool| !visited.contains(tool))
+            .collect::<Vec<_>>();
+        tools.into_iter()
+            .map(|tool| TechTreeNode {
+                id: Some(tool.to_string()),
+                nodes: non_empty(self.tech_tree(tool, visited)),
+            })
+            .collect()
+    }
+
+    fn collect_tools(&self, id: i32, seen: &mut HashSet<i32>, tools: &mut Vec<i32>) {
+        if !seen.insert(id) {
+            return;
+        }
+        let Some(transition) = self.best_transition(id) else {
+            return;
+        };
+        if transition.is_tool() && self.is_craftable(transition.actorID) {
+            if !tools.contains(&transition.actorID) {
+                tools.push(transition.actorID);
+            }
+        } else if transition.actorID > 0 {
+            self.collect_tools(transition.actorID, seen, tools);
+        }
+        if transition.targetID > 0 {
+            self.collect_tools(transition.targetID, seen, tools);
+        }
+    }
+
+    fn transitions_toward(&self, id: i32) -> Vec<TransitionTowardData> {
+        self.transitions
+            .transitions_producing(id)
+            .filter(|t| t.actorID != id && t.targetID != id)
+            .map(|t| TransitionTowardData {
+                actorID: Some(t.actorID.to_string()).filter(|_| t.actorID > 0),
+                targetID: Some(t.targetID.to_string()).filter(|_| t.targetID > 0),
+                newActorID: Some(t.newActorID.to_string()).filter(|_| t.newActorID > 0),
+                newTargetID: Some(t.newTargetID.to_string()).filter(|_| t.newTargetID > 0),
+                hand: Some(t.is_bare_hand()),
+                decay: t.decay_seconds().map(DecayTime::Seconds),
+            })
+            .collect()
+    }
+
+    fn transitions_away(&self, id: i32) -> Vec<TransitionAwayData> {
+        self.transitions
+            .transitions_with_actor(id)
+            .chain(self.transitions.transitions_with_target(id).filter(|t| !t.is_decay() && t.actorID != id))
+            .map(|t| TransitionAwayData {
+                actorID: Some(t.actorID.to_string()).filter(|_| t.actorID > 0),
+                targetID: Some(t.targetID.to_string()).filter(|_| t.targetID > 0),
+                newActorID: Some(t.newActorID.to_string()).filter(|_| t.newActorID > 0),
+                newTargetID: Some(t.newTargetID.to_string()).filter(|_| t.newTargetID > 0),
+                newActorUses: None,
+                newActorWeight: None,
+                targetRemains: Some(t.target_remains()),
+                hand: Some(t.is_bare_hand()),
+                tool: Some(t.is_tool()),
+                decay: t.decay_seconds().map(DecayTime::Seconds),
+            })
+            .collect()
+    }
+
+    fn transitions_timed(&self, id: i32) -> Vec<TransitionTimedData> {
+        self.transitions
+            .transitions_with_target(id)
+            .filter(|t| t.is_decay())
+            .map(|t| TransitionTimedData {
+                targetID: Some(t.targetID.to_string()),
+                newTargetID: Some(t.newTargetID.to_string()).filter(|_| t.newTargetID > 0),
+                decay: t.decay_seconds().map(DecayTime::Seconds),
+            })
+            .collect()
+    }
+}
+
+/// Transitions with category objects (e.g. "@ Any Berry Bowl") replaced by each of their members, the way twotech expands them.
+/// A product that's the same category as an input becomes that input's member, and pattern categories of the same size are
+/// matched up member by member. Any other category product becomes each of its members in turn. Where a member already has
+/// its own transition with the same inputs, that one is kept instead.
+fn expand_category_transitions(transitions: &[Transition], categories: &BTreeMap<i32, CategoryData>) -> Vec<Transition> {
+    let is_category = |id: &i32| categories.contains_key(id);
+    let members = |id: i32| match is_category(&id) {
+        true => expand_category(categories, id).into_iter().map(|member| member.id).collect(),
+        false => vec![id],
+    };
+    let key = |t: &Transition| (t.actorID, t.targetID, t.lastUseActor, t.lastUseTarget);
+    let (generic, mut expanded): (Vec<_>, Vec<_>) = transitions
+        .iter()
+        .cloned()
+        .partition(|t| [t.actorID, t.targetID, t.newActorID, t.newTargetID].iter().any(is_category));
+    let specific = expanded.iter().map(key).collect::<HashSet<_>>();
+    for transition in generic {
+        let actors = members(transition.actorID);
+        let targets = members(transition.targetID);
+        for (actor_index, &actor) in actors.iter().enumerate() {
+            for (target_index, &target) in targets.iter().enumerate() {
+                let products = |product: i32| -> Vec<i32> {
+                    if product == transition.actorID {
+                        return vec![actor];
+                    }
+                    if product == transition.targetID {
+                        return vec![target];
+                    }
+                    let pattern_member = categories.get(&product).filter(|category| category.pattern).and_then(|category| {
+                        [(transition.actorID, actor_index), (transition.targetID, target_index)]
+                            .into_iter()
+                            .find(|(input, _)| categories.get(input).is_some_and(|c| c.pattern && c.objectIDs.len() == category.objectIDs.len()))
+                            .map(|(_, index)| category.objectIDs[index])
+                    });
+                    pattern_member.map_or_else(|| members(product), |member| vec![member])
+                };
+                for new_actor in products(transition.newActorID) {
+                    for new_target in products(transition.newTargetID) {
+                        let member_transition = Transition { actorID: actor, targetID: target, newActorID: new_actor, newTargetID: new_target, ..transition.clone() };
+                        if !specific.contains(&key(&member_transition)) {
+                            expanded.push(member_transition);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    expanded
+}
+
+fn non_empty<T>(values: Vec<T>) -> Option<Vec<T>> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    // Stone and Branch grow naturally. Two stones make a Sharp Stone, which is used (and kept) to cut a Branch into a
+    // Skewer. A Skewer can be turned into a Shaft and back. "@ Any Stone" is a category of Stone and Flint.
+    fn generate(transitions: &[(&str, &str)], categories: &[&str]) -> BTreeMap<String, TwoTechObject> {
+        let objects = [
+            (1, "Stone", true),
+            (2, "Branch", true),
+            (3, "Sharp Stone", false),
+            (4, "Skewer", false),
+            (5, "Shaft", false),
+            (6, "@ Any Stone", false),
+            (7, "Flint", true),
+            (8, "Spear", false),
+        ];
+        let one_life_objects = objects
+            .iter()
+            .map(|&(id, name, natural)| {
+                let mut object = json!({"id": id, "name": name});
+                if natural {
+                    object["mapChance"] = json!({"mapChance": 0.5, "biomes": null});
+                }
+                (id.to_string(), serde_json::from_value(object).unwrap())
+            })
+            .collect::<BTreeMap<_, OneLifeDataObject>>();
+        let transitions = TransitionGraph::new(transitions.iter().map(|(file_name, contents)| Transition::from_file(file_name, contents).unwrap()).collect());
+        let categories = categories
+            .iter()
+            .map(|category| category.parse::<CategoryData>().unwrap())
+            .map(|category| (category.parentID, category))
+            .collect();
+        TwoTechGenerator::new(&one_life_objects, &transitions, &categories).generate()
+    }
+
+    const SKEWER: [(&str, &str); 2] = [("1_1.txt", "0 3"), ("3_2.txt", "3 4")];
+
+    #[test]
+    fn natural_objects_are_depth_zero() {
+        let objects = generate(&SKEWER, &[]);
+        assert_eq!(objects["1"].depth, Some(0));
+        assert_eq!(objects["1"].craftable, Some(false));
+        assert_eq!(objects["1"].recipe, None);
+        // Nothing makes a Shaft here
+        assert_eq!(objects["5"].depth, None);
+        assert_eq!(objects["5"].craftable, Some(false));
+    }
+
+    #[test]
+    fn depth_is_one_more_than_the_deepest_input() {
+        let objects = generate(&SKEWER, &[]);
+        assert_eq!([objects["2"].depth, objects["3"].depth, objects["4"].depth], [Some(0), Some(1), Some(2)]);
+        assert_eq!(objects["4"].craftable, Some(true));
+        let recipe = objects["4"].recipe.as_ref().unwrap();
+        // Both stones are used up
+        assert_eq!(recipe.ingredients.as_deref(), Some(&["1".to_string(), "1".to_string(), "2".to_string()][..]));
+        let steps = recipe.steps.iter().flatten().flatten().map(|step| step.id.clone().unwrap()).collect::<Vec<_>>();
+        assert_eq!(steps, ["3", "4"]);
+    }
+
+    #[test]
+    fn craftable_tools_are_in_the_tech_tree() {
+        let objects = generate(&SKEWER, &[]);
+        let tech_tree = objects["4"].techTree.as_ref().unwrap();
+        assert_eq!(tech_tree.iter().map(|node| node.id.clone().unwrap()).collect::<Vec<_>>(), ["3"]);
+        assert_eq!(objects["3"].techTree, None);
+    }
+
+    #[test]
+    fn cycles_keep_the_shallowest_transition() {
+        let transitions = [SKEWER[0], SKEWER[1], ("0_4.txt", "0 5"), ("0_5.txt", "0 4")];
+        let objects = generate(&transitions, &[]);
+        assert_eq!([objects["4"].depth, objects["5"].depth], [Some(2), Some(3)]);
+        let steps = objects["5"].recipe.as_ref().unwrap().steps.iter().flatten().flatten().count();
+        assert_eq!(steps, 3);
+    }
+
+    #[test]
+    fn category_transitions_are_expanded_into_their_members() {
+        // Any stone, kept, on a Branch makes a Spear
+        let objects = generate(&[("6_2.txt", "6 8")], &["parentID=6\nnumObjects=2\n1\n7"]);
+        assert_eq!(objects["8"].depth, Some(1));
+        assert_eq!(objects["8"].craftable, Some(true));
+        let actors = objects["8"].transitionsToward.iter().flatten().map(|t| t.actorID.clone().unwrap()).collect::<Vec<_>>();
+        assert_eq!(actors, ["1", "7"]);
+        assert!(objects["1"].transitionsAway.iter().flatten().any(|t| t.newTargetID.as_deref() == Some("8")));
+    }
+}
@@ -1,9 +1,12 @@
 #![allow(non_snake_case)]
+#![allow(clippy::to_string_trait_impl)]
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use strum::EnumIter;
 use std::str::FromStr;
 
+use crate::one_life_data_object::BiomeId;
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct TwoTechObject {
     pub id: String,
@@ -39,6 +42,18 @@ pub struct TwoTechObject {
     pub slotSize: Option<f32>,
 }
 
+impl TwoTechObject {
+    /// How long the object lasts when left alone: its quickest timed transition, or Never if it has none
+    pub fn decay_time(&self) -> DecayTime {
+        self.transitionsTimed
+            .iter()
+            .flatten()
+            .filter_map(|t| t.decay.clone())
+            .min()
+            .unwrap_or(DecayTime::Never)
+    }
+}
+
 fn GetNone<T>() -> Option<T> {
     None
 }
@@ -52,10 +67,11 @@ where
     match value {
         serde_json::Value::Number(n) => {
             let move_type = n.as_i64();
-            if move_type.is_none() {
-                return Err(serde::de::Error::custom(&format!("Invalid value for move_type {}, doesn't fit into i64!", n.to_string())));
-            }
-            let move_type = match move_type.unwrap() {
+            let move_type = match move_type {
+                None => return Err(serde::de::Error::custom(format!("Invalid value for move_type {}, doesn't fit into i64!", n))),
+                Some(move_type) => move_type,
+            };
+            let move_type = match move_type {
                 0 => Some(MoveType::None),
                 1 => Some(MoveType::Chase),
                 2 => Some(MoveType::Flee),
@@ -67,10 +83,9 @@ where
                 8 => Some(MoveType::Find),
                 _ => None,
             };
-            if move_type.is_none() {
-                Err(serde::de::Error::custom(&format!("Invalid value for move_type {}, out of range!", n.to_string())))
-            } else {
-                Ok(Some(move_type.unwrap()))
+            match move_type {
+                Some(move_type) => Ok(Some(move_type)),
+                None => Err(serde::de::Error::custom(format!("Invalid value for move_type {}, out of range!", n))),
             }
         }
         serde_json::Value::String(s) => {
@@ -86,13 +101,13 @@ where
                 "find" => Some(MoveType::Find),
                 _ => None
             };
-            if move_type.is_none() {
-                Err(serde::de::Error::custom(&format!("Invalid value for move_type {}, no movement type match found!", s)))
-            } else {
-                Ok(Some(move_type.unwrap()))
+            match move_type {
+                Some(move_type) => Ok(Some(move_type)),
+                None => Err(serde::de::Error::custom(format!("Invalid value for move_type {}, no movement type match found!", s))),
             }
         }
-        serde_json::Value::Null => Ok(Some(MoveType::None)),
+        // Same as a missing moveType, so data reads back the same from the cache as it did from twotech
+        serde_json::Value::Null => Ok(None),
         _ => Err(serde::de::Error::custom("Unexpected value deserializing moveType")),
     }
 }
@@ -138,6 +153,13 @@ pub struct Biome {
     pub spawnChance: Option<f64>,
 }
 
+impl Biome {
+    /// twotech stores biome IDs as strings, e.g. "0" for Grasslands
+    pub fn biome_id(&self) -> Option<BiomeId> {
+        self.id.as_deref()?.parse().ok()
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, EnumIter, PartialEq, Serialize)]
 pub enum MoveType {
     None,
@@ -206,7 +228,7 @@ impl FromStr for MoveType {
 pub struct TransitionTimedData {
     pub targetID: Option<String>,
     pub newTargetID: Option<String>,
-    pub decay: Option<String>,
+    pub decay: Option<DecayTime>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -216,7 +238,7 @@ pub struct TransitionTowardData {
     pub newActorID: Option<String>,
     pub newTargetID: Option<String>,
     pub hand: Option<bool>,
-    pub decay: Option<String>,
+    pub decay: Option<DecayTime>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -230,7 +252,89 @@ pub struct TransitionAwayData {
     pub targetRemains: Option<bool>,
     pub hand: Option<bool>,
     pub tool: Option<bool>,
-    pub decay: Option<String>,
+    pub decay: Option<DecayTime>,
+}
+
+/// How long a timed transition takes, from twotech's decay strings like "30s", "2m" or "1h".
+/// Ordered from quickest to slowest, so an epoch decay or one that never happens sorts after any set time.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum DecayTime {
+    Seconds(u64),
+    // Happens at the end of an epoch rather than after a set time
+    Epoch,
+    Never,
+    // A decay string we can't read, kept as twotech wrote it
+    Unknown(String),
+}
+
+impl DecayTime {
+    /// The wait in seconds, or None for decays that don't happen after a set time
+    pub fn seconds(&self) -> Option<u64> {
+        match self {
+            DecayTime::Seconds(seconds) => Some(*seconds),
+            DecayTime::Epoch | DecayTime::Never | DecayTime::Unknown(_) => None,
+        }
+    }
+}
+
+impl ToString for DecayTime {
+    // Formatted the way twotech does, e.g. "30s", "5m", "2h"
+    fn to_string(&self) -> String {
+        match self {
+            DecayTime::Seconds(seconds) if *seconds > 0 && seconds % 3600 == 0 => format!("{}h", seconds / 3600),
+            DecayTime::Seconds(seconds) if *seconds > 60 && seconds % 60 == 0 => format!("{}m", seconds / 60),
+            DecayTime::Seconds(seconds) => format!("{seconds}s"),
+            DecayTime::Epoch => "epoch".to_string(),
+            DecayTime::Never => "never".to_string(),
+            DecayTime::Unknown(s) => s.clone(),
+        }
+    }
+}
+
+impl FromStr for DecayTime {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        if s.contains("epoch") {
+            return Ok(DecayTime::Epoch);
+        }
+        if s.is_empty() || s == "never" || s == "0" {
+            return Ok(DecayTime::Never);
+        }
+        let unit_start = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+        let (amount, unit) = s.split_at(unit_start);
+        let amount = amount
+            .parse::<f64>()
+            .map_err(|_| anyhow!("Could not parse decay time \"{s}\" (expected e.g. 30s, 5m, 1h or epoch)"))?;
+        let multiplier = match unit.trim() {
+            "" | "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+            _ => return Err(anyhow!("Unknown decay time unit in \"{s}\" (expected s, m or h)")),
+        };
+        Ok(DecayTime::Seconds((amount * multiplier as f64).round() as u64))
+    }
+}
+
+impl Serialize for DecayTime {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for DecayTime {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // twotech may write decays we don't know how to read yet, which shouldn't stop the rest of its data loading
+        let s = String::deserialize(deserializer)?;
+        Ok(DecayTime::from_str(&s).unwrap_or(DecayTime::Unknown(s)))
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -320,3 +424,39 @@ pub struct TechTreeNode {
     pub id: Option<String>,
     pub nodes: Option<Vec<TechTreeNode>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decay(s: &str) -> DecayTime {
+        serde_json::from_value(serde_json::Value::String(s.to_string())).unwrap()
+    }
+
+    #[test]
+    fn decay_times() {
+        assert_eq!(decay("30s"), DecayTime::Seconds(30));
+        assert_eq!(decay("5m"), DecayTime::Seconds(5 * 60));
+        assert_eq!(decay("2h"), DecayTime::Seconds(2 * 60 * 60));
+        assert_eq!(decay("1.5h"), DecayTime::Seconds(90 * 60));
+        assert_eq!(decay("epoch"), DecayTime::Epoch);
+        assert_eq!(decay(""), DecayTime::Never);
+    }
+
+    #[test]
+    fn unreadable_decay_time_is_kept() {
+        assert!("3 fortnights".parse::<DecayTime>().is_err());
+        assert_eq!(decay("3 fortnights"), DecayTime::Unknown("3 fortnights".to_string()));
+        assert_eq!(decay("3 fortnights").seconds(), None);
+        assert_eq!(serde_json::to_string(&decay("3 fortnights")).unwrap(), "\"3 fortnights\"");
+        let transition: TransitionAwayData = serde_json::from_str(r#"{"targetID": "1", "decay": "soon"}"#).unwrap();
+        assert_eq!(transition.decay, Some(DecayTime::Unknown("soon".to_string())));
+    }
+
+    #[test]
+    fn decay_times_order_quickest_first() {
+        assert!(decay("59m") < decay("1h"));
+        assert!(decay("1h") < decay("epoch"));
+        assert!(decay("epoch") < decay(""));
+    }
+}
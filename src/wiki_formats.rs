@@ -1,13 +1,227 @@
 use std::collections::BTreeMap;
-use std::ops::{Div, Mul};
+use std::sync::OnceLock;
 
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::filter_expression::{as_number, lookup_field};
+use crate::output_format::cell_text;
+use crate::query_results::QueryResults;
 use crate::SharedGameObject;
-use crate::twotech_object::MoveType;
 
-pub fn _generate_wiki_cards(shared_game_objects: &BTreeMap<String, SharedGameObject>) -> String {
+// Decimal places numbers are rounded to when a template doesn't say, enough to hide float noise like 0.20000000298
+const DEFAULT_DECIMALS: u32 = 6;
+
+// The table layouts thol-helper ships with, by name. Each one is also an example of the template file format.
+const BUILT_IN_TEMPLATES: [(&str, &str); 6] = [
+    ("slots", include_str!("../wiki-templates/slots.txt")),
+    ("food", include_str!("../wiki-templates/food.txt")),
+    ("clothing", include_str!("../wiki-templates/clothing.txt")),
+    ("movers", include_str!("../wiki-templates/movers.txt")),
+    ("single-mover", include_str!("../wiki-templates/single-mover.txt")),
+    ("cards", include_str!("../wiki-templates/cards.txt")),
+];
+
+/// A wiki table layout: a header, a row repeated for every object, and a footer.
+///
+/// Template files are split into sections by `[header]`, `[row]` and `[footer]` lines (only `[row]` is required).
+/// Rows fill in placeholders like `{{name}}` or `{{onelife.speedMult}}`, with fields looked up the same way as in
+/// `--where` expressions. Placeholders start with a lowercase letter, so wiki templates like `{{Card|...}}` are left
+/// alone. Filters after the field, separated by `:`, are applied in order:
+/// - `number` or `number=N` rounds to N decimal places (6 by default) and drops trailing zeros
+/// - `percent` or `percent=N` does the same to the value times 100, and adds a `%`
+/// - `index=N` picks element N of a list (from 0), and `sum` adds a list up
+/// - `default=TEXT` fills in TEXT if the value is missing so far
+///
+/// e.g. `{{foodValue:index=0:default=0}}` or `{{insulation:default=0:percent=2}}`. A `\` makes the character after it
+/// part of the filter rather than a separator, so `{{onelife.speedMult:default=N\:A}}` fills in `N:A`.
+#[derive(Clone, Debug)]
+pub struct WikiTemplate {
+    pub header: String,
+    pub row: Vec<TemplatePiece>,
+    pub footer: String,
+}
+
+#[derive(Clone, Debug)]
+pub enum TemplatePiece {
+    Text(String),
+    Placeholder { field: String, filters: Vec<TemplateFilter> },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TemplateFilter {
+    Number(u32),
+    Percent(u32),
+    Index(usize),
+    Sum,
+    Default(String),
+}
+
+impl WikiTemplate {
+    pub fn built_in_names() -> Vec<&'static str> {
+        BUILT_IN_TEMPLATES.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// A built-in template by name, or else a template file
+    pub fn load(name_or_path: &str) -> Result<Self> {
+        match BUILT_IN_TEMPLATES.iter().find(|(name, _)| name.eq_ignore_ascii_case(name_or_path)) {
+            Some((_, source)) => Self::parse(source),
+            None => {
+                let source = std::fs::read_to_string(name_or_path).with_context(|| {
+                    format!("{name_or_path} is neither a built-in wiki template ({}) nor a readable template file", Self::built_in_names().join(", "))
+                })?;
+                Self::parse(&source).with_context(|| format!("Could not parse wiki template {name_or_path}"))
+            }
+        }
+    }
+
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut sections: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        let mut current_section = "header";
+        for line in source.lines() {
+            match line.trim() {
+                "[header]" => current_section = "header",
+                "[row]" => current_section = "row",
+                "[footer]" => current_section = "footer",
+                _ => sections.entry(current_section).or_default().push(line),
+            }
+        }
+        let section = |name: &str| sections.get(name).map(|lines| lines.join("\n").trim_matches('\n').to_string());
+        let row = section("row").filter(|row| !row.is_empty()).ok_or(anyhow!("Wiki template has no [row] section"))?;
+        Ok(WikiTemplate {
+            header: section("header").unwrap_or_default(),
+            row: parse_row(&row)?,
+            footer: section("footer").unwrap_or_default(),
+        })
+    }
+
+    pub fn render_row(&self, shared_obj: &SharedGameObject) -> String {
+        self.row
+            .iter()
+            .map(|piece| match piece {
+                TemplatePiece::Text(text) => text.clone(),
+                TemplatePiece::Placeholder { field, filters } => {
+                    cell_text(&filters.iter().fold(lookup_field(shared_obj.as_json(), field), |value, filter| filter.apply(value)))
+                }
+            })
+            .collect()
+    }
+
+    /// The whole table, with one row per object in the order given
+    pub fn render<'a>(&self, objects: impl IntoIterator<Item = &'a SharedGameObject>) -> String {
+        let mut output = vec![self.header.clone()];
+        output.extend(objects.into_iter().map(|shared_obj| self.render_row(shared_obj)));
+        output.push(self.footer.clone());
+        output.into_iter().filter(|part| !part.is_empty()).collect::<Vec<_>>().join("\n")
+    }
+
+    /// A table per group, each under a wiki heading, or just the one table if the results aren't grouped
+    pub fn render_results(&self, results: &QueryResults) -> String {
+        if !results.is_grouped() {
+            return self.render(results.objects());
+        }
+        results
+            .groups
+            .iter()
+            .map(|group| format!("== {} ==\n{}", group.key, self.render(group.objects.iter().copied())))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+impl TemplateFilter {
+    fn parse(filter: &str) -> Result<Self> {
+        let (name, argument) = match filter.split_once('=') {
+            Some((name, argument)) => (name.trim(), Some(argument)),
+            None => (filter.trim(), None),
+        };
+        let decimals = |argument: Option<&str>| match argument {
+            Some(decimals) => decimals.trim().parse::<u32>().map_err(|_| anyhow!("Invalid number of decimal places in {filter}")),
+            None => Ok(DEFAULT_DECIMALS),
+        };
+        match name {
+            "number" => Ok(TemplateFilter::Number(decimals(argument)?)),
+            "percent" => Ok(TemplateFilter::Percent(decimals(argument)?)),
+            "index" => argument
+                .and_then(|index| index.trim().parse().ok())
+                .map(TemplateFilter::Index)
+                .ok_or(anyhow!("Invalid index in {filter}")),
+            "sum" => Ok(TemplateFilter::Sum),
+            "default" => Ok(TemplateFilter::Default(argument.unwrap_or_default().to_string())),
+            _ => Err(anyhow!("Unknown wiki template filter {filter} (expected number, percent, index, sum or default)")),
+        }
+    }
+
+    // Missing values pass through everything but default, so a default anywhere in the chain catches them
+    fn apply(&self, value: Value) -> Value {
+        match (self, value) {
+            (TemplateFilter::Default(text), Value::Null) => Value::String(text.clone()),
+            (TemplateFilter::Default(_), value) => value,
+            (_, Value::Null) => Value::Null,
+            (TemplateFilter::Number(decimals), value) => as_number(&value).map_or(value, |n| Value::String(round(n, *decimals))),
+            (TemplateFilter::Percent(decimals), value) => {
+                as_number(&value).map_or(value, |n| Value::String(format!("{}%", round(n * 100.0, *decimals))))
+            }
+            (TemplateFilter::Index(index), Value::Array(elements)) => elements.get(*index).cloned().unwrap_or_default(),
+            (TemplateFilter::Index(_), value) => value,
+            (TemplateFilter::Sum, Value::Array(elements)) => {
+                Value::String(round(elements.iter().filter_map(as_number).sum(), DEFAULT_DECIMALS))
+            }
+            (TemplateFilter::Sum, value) => value,
+        }
+    }
+}
+
+fn parse_row(row: &str) -> Result<Vec<TemplatePiece>> {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    let placeholder = PLACEHOLDER.get_or_init(|| Regex::new(r"\{\{([a-z][\w.]*)((?::(?:\\.|[^{}:\\])*)*)\}\}").unwrap());
+    let mut pieces = Vec::new();
+    let mut text_start = 0;
+    for captures in placeholder.captures_iter(row) {
+        let whole = captures.get(0).unwrap();
+        if whole.start() > text_start {
+            pieces.push(TemplatePiece::Text(row[text_start..whole.start()].to_string()));
+        }
+        let filters = split_filters(&captures[2])
+            .iter()
+            .skip(1)
+            .map(|filter| TemplateFilter::parse(filter))
+            .collect::<Result<Vec<_>>>()?;
+        pieces.push(TemplatePiece::Placeholder { field: captures[1].to_string(), filters });
+        text_start = whole.end();
+    }
+    if text_start < row.len() {
+        pieces.push(TemplatePiece::Text(row[text_start..].to_string()));
+    }
+    Ok(pieces)
+}
+
+// Splits on each `:` that isn't escaped with a `\`, dropping the escapes
+fn split_filters(filters: &str) -> Vec<String> {
+    let mut split = vec![String::new()];
+    let mut chars = filters.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => split.last_mut().unwrap().extend(chars.next()),
+            ':' => split.push(String::new()),
+            c => split.last_mut().unwrap().push(c),
+        }
+    }
+    split
+}
+
+// Display drops the trailing zeros, e.g. 20 rather than 20.000000
+fn round(n: f64, decimals: u32) -> String {
+    let scale = 10f64.powi(decimals as i32);
+    ((n * scale).round() / scale).to_string()
+}
+
+pub fn _generate_wiki_cards<'a>(shared_game_objects: impl IntoIterator<Item = &'a SharedGameObject>) -> String {
     let names_encountered = Vec::new();
     let mut output = Vec::new();
-    for (id, obj) in shared_game_objects {
+    for obj in shared_game_objects {
+        let id = &obj.twotech_data.id;
         let name = &obj.twotech_data.name;
         if !names_encountered.contains(name) {
             if name.contains(" - ") {
@@ -22,72 +236,96 @@ pub fn _generate_wiki_cards(shared_game_objects: &BTreeMap<String, SharedGameObj
     output.join("\n")
 }
 
-pub fn _wiki_format_card_template_object_id(obj: &SharedGameObject) -> String {
-    let mut output = Vec::new();
-    let id = &obj.twotech_data.id;
-    let name = &obj.twotech_data.name;
-    // if name.contains(" - ") {
-    //     let name_portion = name.split(" - ").collect::<Vec<_>>()[0];
-    //     output.push(format!("| {name_portion} = https://twotech.twohoursonelife.com/{id}"))
-    // }
-    output.push(format!("| {name} = https://twotech.twohoursonelife.com/{id}"));
-    output.join("\n")
-}
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
 
-pub fn _wiki_format_line_slot_item(obj: &SharedGameObject) -> String {
-    format!("|-
-|{{{{Card|{}}}}}
-|{}
-|{}",
-        obj.twotech_data.name,
-        obj.twotech_data.numSlots.map(|n| n.to_string()).unwrap_or("0".to_string()),
-        obj.twotech_data.slotSize.map(|n| n.to_string()).unwrap_or("0".to_string()),
-    )
-}
+    use super::*;
 
-pub fn _wiki_format_line_food(obj: &SharedGameObject) -> String {
-    let food_value = obj.twotech_data.foodValue.clone().unwrap_or(vec![0,0]);
-    format!("|-
-|{{{{Card|{}}}}}
-|{}
-|{}
-|{}",
-        obj.twotech_data.name,
-        food_value[0].to_string(),
-        food_value[1].to_string(),
-        food_value.iter().sum::<i32>()
-    )
-}
+    fn object(twotech_data: Value, one_life_game_data: Value) -> SharedGameObject {
+        serde_json::from_value(json!({ "twotech_data": twotech_data, "one_life_game_data": one_life_game_data })).unwrap()
+    }
 
-pub fn _wiki_format_line_clothing_with_slots(obj: &SharedGameObject) -> String {
-    format!("|-
-|{{{{Card|{}}}}}
-|{:1.}%
-|{}",
-        obj.twotech_data.name,
-        obj.twotech_data.insulation.unwrap_or(0.0).mul(100.0).mul(1000000.0).round().div(1000000.0),
-        obj.twotech_data.numSlots.map(|n| n.to_string()).unwrap_or("0".to_string())
-    )
-}
+    fn backpack() -> SharedGameObject {
+        object(
+            json!({"id": "8", "name": "Backpack", "numSlots": 4, "slotSize": 0.5, "insulation": 0.2}),
+            json!({"id": 8, "name": "Backpack", "speedMult": 1.0, "rValue": 0.5, "clothing": {"clothing": "Pack"}}),
+        )
+    }
 
-pub fn _wiki_format_line_movers(obj: &SharedGameObject) -> String {
-    format!("|-
-|[https://twotech.twohoursonelife.com/{} {}]
-|{}
-|{}",
-        obj.twotech_data.id,
-        obj.twotech_data.name,
-        obj.twotech_data.moveType.as_ref().unwrap_or(&MoveType::None).to_string(),
-        obj.one_life_game_data.speedMult.map_or("N/A".to_string(), |sm| sm.to_string()),
-    )
-}
+    fn gooseberry() -> SharedGameObject {
+        object(json!({"id": "6", "name": "Gooseberry", "foodValue": [3, 1]}), json!({"id": 6, "name": "Gooseberry"}))
+    }
+
+    fn wolf() -> SharedGameObject {
+        object(json!({"id": "9", "name": "Wolf", "moveType": 1}), json!({"id": 9, "name": "Wolf", "speedMult": 1.5}))
+    }
 
-pub fn _wiki_format_line_single_mover_type(obj: &SharedGameObject) -> String {
-    format!("|-
-|[https://twotech.twohoursonelife.com/{} {}]
-|{}",
-        obj.twotech_data.id,
-        obj.twotech_data.name,
-        obj.one_life_game_data.speedMult.map_or("N/A".to_string(), |sm| sm.to_string()),
-    )
+    // An object with none of the fields the built-in templates fill in
+    fn stone() -> SharedGameObject {
+        object(json!({"id": "1", "name": "Stone"}), json!({"id": 1, "name": "Stone"}))
+    }
+
+    fn render_row(template: &str, shared_obj: &SharedGameObject) -> String {
+        WikiTemplate::load(template).unwrap().render_row(shared_obj)
+    }
+
+    // The expected rows are what the built-in tables looked like before they were templates
+
+    #[test]
+    fn slots() {
+        assert_eq!(render_row("slots", &backpack()), "|-\n|{{Card|Backpack}}\n|4\n|0.5");
+        assert_eq!(render_row("slots", &stone()), "|-\n|{{Card|Stone}}\n|0\n|0");
+    }
+
+    #[test]
+    fn food() {
+        assert_eq!(render_row("food", &gooseberry()), "|-\n|{{Card|Gooseberry}}\n|3\n|1\n|4");
+        assert_eq!(render_row("food", &stone()), "|-\n|{{Card|Stone}}\n|0\n|0\n|0");
+    }
+
+    #[test]
+    fn clothing() {
+        // Computed from the rValue (0.5 × 0.1 for a pack), rather than twotech's insulation
+        assert_eq!(render_row("clothing", &backpack()), "|-\n|{{Card|Backpack}}\n|5%\n|4");
+        assert_eq!(render_row("clothing", &stone()), "|-\n|{{Card|Stone}}\n|0%\n|0");
+    }
+
+    #[test]
+    fn movers() {
+        assert_eq!(render_row("movers", &wolf()), "|-\n|[https://twotech.twohoursonelife.com/9 Wolf]\n|Chase\n|1.5");
+        assert_eq!(render_row("movers", &backpack()), "|-\n|[https://twotech.twohoursonelife.com/8 Backpack]\n|None\n|1");
+        assert_eq!(render_row("movers", &stone()), "|-\n|[https://twotech.twohoursonelife.com/1 Stone]\n|None\n|N/A");
+    }
+
+    #[test]
+    fn single_mover() {
+        assert_eq!(render_row("single-mover", &wolf()), "|-\n|[https://twotech.twohoursonelife.com/9 Wolf]\n|1.5");
+        assert_eq!(render_row("single-mover", &stone()), "|-\n|[https://twotech.twohoursonelife.com/1 Stone]\n|N/A");
+    }
+
+    #[test]
+    fn cards() {
+        assert_eq!(render_row("cards", &wolf()), "| Wolf = https://twotech.twohoursonelife.com/9");
+    }
+
+    #[test]
+    fn whole_table() {
+        let template = WikiTemplate::load("single-mover").unwrap();
+        assert_eq!(
+            template.render([&wolf(), &stone()]),
+            "{| class=\"wikitable sortable\"\n! Creature !! Speed\n\
+             |-\n|[https://twotech.twohoursonelife.com/9 Wolf]\n|1.5\n\
+             |-\n|[https://twotech.twohoursonelife.com/1 Stone]\n|N/A\n\
+             |}"
+        );
+    }
+
+    #[test]
+    fn escaped_colons() {
+        let template = WikiTemplate::parse("[row]\n{{onelife.speedMult:default=N\\:A}} {{name:default=a\\\\b}}").unwrap();
+        assert_eq!(template.render_row(&stone()), "N:A Stone");
+        assert_eq!(template.render_row(&wolf()), "1.5 Wolf");
+        assert_eq!(split_filters(r":default=a\\b\:c:sum"), ["", r"default=a\b:c", "sum"]);
+    }
 }